@@ -0,0 +1,389 @@
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{commands::baidu_translate::TextTranslateResult, db::DbPool, error::AppError};
+
+/// 缓存有效期：译文可能随服务端模型更新而变化，不宜永久保存
+const CACHE_TTL_SECS: i64 = 60 * 60 * 24 * 30;
+
+/// 最多保留的条目数，超出后按 last_hit_at 淘汰最久未使用的
+const CACHE_MAX_ENTRIES: i64 = 20_000;
+
+/// 一次翻译请求在缓存里的身份
+pub struct CacheKey<'a> {
+  pub provider: &'a str,
+  pub from: &'a str,
+  pub to: &'a str,
+  pub text: &'a str,
+  pub term_ids: Option<&'a str>,
+}
+
+impl CacheKey<'_> {
+  /// sha256(provider \0 from \0 to \0 规范化原文 \0 term_ids)
+  fn digest(&self) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+      self.provider,
+      self.from,
+      self.to,
+      &normalize(self.text),
+      self.term_ids.unwrap_or(""),
+    ] {
+      hasher.update(part);
+      hasher.update([0u8]);
+    }
+    format!("{:x}", hasher.finalize())
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheStats {
+  pub entries: i64,
+  /// 累计命中次数
+  pub hits: i64,
+  /// 命中缓存省下的计费字符数
+  pub saved_chars: i64,
+  /// 缓存内容大致占用的字节数
+  pub size_bytes: i64,
+  pub oldest_at: Option<i64>,
+  pub max_entries: i64,
+  pub ttl_secs: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CachePurgePayload {
+  /// 只清理某个服务的缓存，不传表示全部
+  pub provider: Option<String>,
+  /// 只清理已过期的条目
+  pub expired_only: Option<bool>,
+}
+
+/// 查缓存：命中时顺带刷新 last_hit_at / hit_count
+pub fn get(pool: &DbPool, key: &CacheKey<'_>) -> Result<Option<TextTranslateResult>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let now = Utc::now().timestamp();
+  let cache_key = key.digest();
+
+  let result: Option<String> = conn
+    .query_row(
+      "SELECT result FROM translation_cache WHERE cache_key = ?1 AND created_at > ?2",
+      params![cache_key, now - CACHE_TTL_SECS],
+      |r| r.get(0),
+    )
+    .optional()
+    .map_err(|e| AppError::Db(format!("query cache failed: {e}")))?;
+
+  let Some(result) = result else {
+    return Ok(None);
+  };
+
+  // 结构变化导致旧数据反序列化失败时删掉这条、当作未命中，不计入命中统计
+  let Ok(result) = serde_json::from_str::<TextTranslateResult>(&result) else {
+    conn
+      .execute("DELETE FROM translation_cache WHERE cache_key = ?1", params![cache_key])
+      .map_err(|e| AppError::Db(format!("delete cache failed: {e}")))?;
+    return Ok(None);
+  };
+
+  conn
+    .execute(
+      "UPDATE translation_cache SET hit_count = hit_count + 1, last_hit_at = ?2 WHERE cache_key = ?1",
+      params![cache_key, now],
+    )
+    .map_err(|e| AppError::Db(format!("update cache failed: {e}")))?;
+
+  Ok(Some(result))
+}
+
+/// 写缓存（覆盖同 key 的旧条目），然后清理过期和超出容量的条目
+pub fn put(pool: &DbPool, key: &CacheKey<'_>, result: &TextTranslateResult) -> Result<(), AppError> {
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let now = Utc::now().timestamp();
+  let result_json = serde_json::to_string(result)?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+
+  tx.execute(
+    r#"
+    INSERT INTO translation_cache(cache_key, provider, from_lang, to_lang, source_chars, result, created_at, last_hit_at)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+    ON CONFLICT(cache_key) DO UPDATE SET
+      result = excluded.result,
+      created_at = excluded.created_at,
+      last_hit_at = excluded.last_hit_at
+    "#,
+    params![
+      key.digest(),
+      key.provider,
+      key.from,
+      key.to,
+      key.text.chars().count() as i64,
+      result_json,
+      now
+    ],
+  )
+  .map_err(|e| AppError::Db(format!("insert cache failed: {e}")))?;
+
+  tx.execute(
+    "DELETE FROM translation_cache WHERE created_at <= ?1",
+    params![now - CACHE_TTL_SECS],
+  )
+  .map_err(|e| AppError::Db(format!("evict cache failed: {e}")))?;
+
+  tx.execute(
+    r#"
+    DELETE FROM translation_cache WHERE cache_key IN (
+      SELECT cache_key FROM translation_cache
+      ORDER BY last_hit_at ASC
+      LIMIT max(0, (SELECT COUNT(*) FROM translation_cache) - ?1)
+    )
+    "#,
+    params![CACHE_MAX_ENTRIES],
+  )
+  .map_err(|e| AppError::Db(format!("evict cache failed: {e}")))?;
+
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(())
+}
+
+pub fn stats(pool: &DbPool) -> Result<CacheStats, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .query_row(
+      r#"
+      SELECT
+        COUNT(*),
+        COALESCE(SUM(hit_count), 0),
+        COALESCE(SUM(hit_count * source_chars), 0),
+        COALESCE(SUM(length(result) + length(cache_key)), 0),
+        MIN(created_at)
+      FROM translation_cache
+      "#,
+      [],
+      |r| {
+        Ok(CacheStats {
+          entries: r.get(0)?,
+          hits: r.get(1)?,
+          saved_chars: r.get(2)?,
+          size_bytes: r.get(3)?,
+          oldest_at: r.get(4)?,
+          max_entries: CACHE_MAX_ENTRIES,
+          ttl_secs: CACHE_TTL_SECS,
+        })
+      },
+    )
+    .map_err(|e| AppError::Db(format!("query cache stats failed: {e}")))
+}
+
+/// 清理缓存，返回删除的条目数
+pub fn purge(pool: &DbPool, payload: &CachePurgePayload) -> Result<usize, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let expired_before = if payload.expired_only.unwrap_or(false) {
+    Utc::now().timestamp() - CACHE_TTL_SECS
+  } else {
+    i64::MAX
+  };
+
+  conn
+    .execute(
+      "DELETE FROM translation_cache WHERE (?1 IS NULL OR provider = ?1) AND created_at <= ?2",
+      params![payload.provider, expired_before],
+    )
+    .map_err(|e| AppError::Db(format!("purge cache failed: {e}")))
+}
+
+/// 只做不改变语义的规范化：统一换行符、去掉首尾空白
+fn normalize(text: &str) -> String {
+  text.replace("\r\n", "\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use r2d2_sqlite::SqliteConnectionManager;
+
+  /// 内存库只能有一个连接（每个连接都是独立的数据库）
+  fn pool() -> DbPool {
+    let pool = r2d2::Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory())
+      .unwrap();
+    crate::db::migrate::migrate(&mut pool.get().unwrap()).unwrap();
+    pool
+  }
+
+  fn key(text: &str) -> CacheKey<'_> {
+    CacheKey {
+      provider: "baidu",
+      from: "en",
+      to: "zh",
+      text,
+      term_ids: None,
+    }
+  }
+
+  fn result(dst: &str) -> TextTranslateResult {
+    TextTranslateResult {
+      from: "en".to_string(),
+      to: "zh".to_string(),
+      dst: dst.to_string(),
+      segments: Vec::new(),
+      raw: serde_json::Value::Null,
+    }
+  }
+
+  fn entries(pool: &DbPool) -> i64 {
+    stats(pool).unwrap().entries
+  }
+
+  #[test]
+  fn hit_after_put_with_normalized_text() {
+    let pool = pool();
+    put(&pool, &key("hello\r\nworld"), &result("你好\n世界")).unwrap();
+
+    assert_eq!(get(&pool, &key("  hello\nworld ")).unwrap().unwrap().dst, "你好\n世界");
+    assert!(get(&pool, &key("hello world")).unwrap().is_none());
+    let other_terms = CacheKey {
+      term_ids: Some("1"),
+      ..key("hello\nworld")
+    };
+    assert!(get(&pool, &other_terms).unwrap().is_none());
+
+    let stats = stats(&pool).unwrap();
+    assert_eq!((stats.entries, stats.hits, stats.saved_chars), (1, 1, 12));
+  }
+
+  #[test]
+  fn undecodable_entry_is_dropped_as_miss() {
+    let pool = pool();
+    put(&pool, &key("hello"), &result("你好")).unwrap();
+    put(&pool, &key("other"), &result("其他")).unwrap();
+    pool
+      .get()
+      .unwrap()
+      .execute(
+        "UPDATE translation_cache SET result = '{\"dst\":1}' WHERE cache_key = ?1",
+        params![key("hello").digest()],
+      )
+      .unwrap();
+
+    assert!(get(&pool, &key("hello")).unwrap().is_none());
+    let after_miss = stats(&pool).unwrap();
+    assert_eq!((after_miss.entries, after_miss.hits), (1, 0));
+
+    // 重新写入后正常命中
+    put(&pool, &key("hello"), &result("你好")).unwrap();
+    assert_eq!(get(&pool, &key("hello")).unwrap().unwrap().dst, "你好");
+    assert_eq!(stats(&pool).unwrap().hits, 1);
+  }
+
+  #[test]
+  fn expired_entries_miss_and_are_purged() {
+    let pool = pool();
+    put(&pool, &key("old"), &result("旧")).unwrap();
+    put(&pool, &key("new"), &result("新")).unwrap();
+    pool
+      .get()
+      .unwrap()
+      .execute(
+        "UPDATE translation_cache SET created_at = created_at - ?1 WHERE cache_key = ?2",
+        params![CACHE_TTL_SECS, key("old").digest()],
+      )
+      .unwrap();
+
+    assert!(get(&pool, &key("old")).unwrap().is_none());
+    assert!(get(&pool, &key("new")).unwrap().is_some());
+
+    let payload = CachePurgePayload {
+      provider: None,
+      expired_only: Some(true),
+    };
+    assert_eq!(purge(&pool, &payload).unwrap(), 1);
+    assert_eq!(entries(&pool), 1);
+
+    // 重新写入会刷新 created_at
+    put(&pool, &key("old"), &result("旧")).unwrap();
+    assert!(get(&pool, &key("old")).unwrap().is_some());
+  }
+
+  #[test]
+  fn put_evicts_expired_entries() {
+    let pool = pool();
+    put(&pool, &key("old"), &result("旧")).unwrap();
+    pool
+      .get()
+      .unwrap()
+      .execute("UPDATE translation_cache SET created_at = created_at - ?1", params![CACHE_TTL_SECS])
+      .unwrap();
+
+    put(&pool, &key("new"), &result("新")).unwrap();
+    assert_eq!(entries(&pool), 1);
+  }
+
+  #[test]
+  fn evicts_least_recently_used_over_capacity() {
+    let pool = pool();
+    let now = Utc::now().timestamp();
+    {
+      let mut conn = pool.get().unwrap();
+      let tx = conn.transaction().unwrap();
+      for i in 0..CACHE_MAX_ENTRIES {
+        tx.execute(
+          r#"
+          INSERT INTO translation_cache(cache_key, provider, from_lang, to_lang, source_chars, result, created_at, last_hit_at)
+          VALUES (?1, 'baidu', 'en', 'zh', 1, '{}', ?2, ?3)
+          "#,
+          params![format!("key-{i}"), now, now - CACHE_MAX_ENTRIES + i],
+        )
+        .unwrap();
+      }
+      tx.commit().unwrap();
+    }
+
+    // 满了之后每写一条就淘汰 last_hit_at 最早的一条
+    put(&pool, &key("one"), &result("一")).unwrap();
+    put(&pool, &key("two"), &result("二")).unwrap();
+    assert_eq!(entries(&pool), CACHE_MAX_ENTRIES);
+
+    let conn = pool.get().unwrap();
+    let exists = |cache_key: &str| -> bool {
+      conn
+        .query_row(
+          "SELECT COUNT(*) FROM translation_cache WHERE cache_key = ?1",
+          params![cache_key],
+          |r| r.get::<_, i64>(0),
+        )
+        .unwrap()
+        == 1
+    };
+    assert!(!exists("key-0"));
+    assert!(!exists("key-1"));
+    assert!(exists("key-2"));
+    assert!(exists(&key("one").digest()));
+    drop(conn);
+
+    // 命中会刷新 last_hit_at，最久未使用的是 key-2
+    assert!(get(&pool, &key("one")).unwrap().is_some());
+    put(&pool, &key("three"), &result("三")).unwrap();
+    assert_eq!(entries(&pool), CACHE_MAX_ENTRIES);
+    assert!(get(&pool, &key("one")).unwrap().is_some());
+  }
+}
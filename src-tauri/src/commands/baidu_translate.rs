@@ -1,51 +1,97 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{
+  future::Future,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use base64::{engine::general_purpose, Engine as _};
 use reqwest::{multipart, Client};
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tauri::State;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Mutex;
 
 use crate::{
+  cache::{self, CacheKey},
   db::DbPool,
+  doc_job::DocJob,
   error::AppError,
-  settings::{ApiKeysForm, NetworkProxyForm, ProxyMode, ProxyProtocol},
+  http::HttpClientState,
+  settings::{ApiKeysForm, ProxyService},
+  translate::{
+    batch::BatchPlan,
+    upload::{self, UploadFile, UploadSource},
+    ProviderKind, TranslateContext, TranslateRequest,
+  },
 };
 
-/// ======= 百度 API endpoints（来自你给的 PDF）=======
-/// 文本翻译（通用版）
-/// POST https://aip.baidubce.com/rpc/2.0/mt/texttrans/v1?access_token=xxx :contentReference[oaicite:6]{index=6}
-///
-/// 文档翻译创建
-/// POST https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/create?access_token=xxx :contentReference[oaicite:7]{index=7}
-///
-/// 文档翻译查询
-/// POST https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/query?access_token=xxx :contentReference[oaicite:8]{index=8}
-///
-/// 图片翻译（multipart）
-/// POST https://aip.baidubce.com/file/2.0/mt/pictrans/v1?access_token=xxx :contentReference[oaicite:9]{index=9}
+// ======= 百度 API endpoints（来自你给的 PDF）=======
+// 文本翻译（通用版）
+// POST https://aip.baidubce.com/rpc/2.0/mt/texttrans/v1?access_token=xxx :contentReference[oaicite:6]{index=6}
+//
+// 文档翻译创建
+// POST https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/create?access_token=xxx :contentReference[oaicite:7]{index=7}
+//
+// 文档翻译查询
+// POST https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/query?access_token=xxx :contentReference[oaicite:8]{index=8}
+//
+// 图片翻译（multipart）
+// POST https://aip.baidubce.com/file/2.0/mt/pictrans/v1?access_token=xxx :contentReference[oaicite:9]{index=9}
 
 const BAIDU_OAUTH_URL: &str = "https://aip.baidubce.com/oauth/2.0/token";
 const BAIDU_TEXTTRANS_URL: &str = "https://aip.baidubce.com/rpc/2.0/mt/texttrans/v1";
 const BAIDU_DOC_CREATE_URL: &str = "https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/create";
-const BAIDU_DOC_QUERY_URL: &str = "https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/query";
+pub(crate) const BAIDU_DOC_QUERY_URL: &str = "https://aip.baidubce.com/rpc/2.0/mt/v2/doc-translation/query";
 const BAIDU_PICTRANS_URL: &str = "https://aip.baidubce.com/file/2.0/mt/pictrans/v1";
 
+/// 图片贴合结果保存在系统临时目录下的这个子目录
+const PIC_TEMP_DIR: &str = "adui-tools-pictrans";
+
+/// 临时目录里超过这个时长的贴合图片在下次保存时清理掉
+const PIC_TEMP_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 通用文本翻译单次请求的长度上限是 6000 字节，留一点余量
+const BAIDU_TEXT_MAX_BYTES: usize = 5800;
+
+/// 批量翻译默认 QPS（个人认证账号的最低配额），前端可按实际配额调整
+const DEFAULT_BATCH_QPS: u32 = 1;
+
+/// 批量翻译被限流时的最大重试次数
+const BATCH_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// 批量翻译进度事件
+pub const BATCH_PROGRESS_EVENT: &str = "baidu-batch-translate:progress";
+
 /// token 有效期 30 天，建议提前一点刷新:contentReference[oaicite:10]{index=10}
 const TOKEN_REFRESH_SAFETY_WINDOW: Duration = Duration::from_secs(60 * 60 * 24); // 提前 1 天刷新
 
 #[derive(Debug)]
 pub struct BaiduTokenState {
   inner: Mutex<Option<CachedToken>>,
+  /// 刷新锁：并发请求同时发现 token 失效时，只有一个去调 OAuth，其余等它刷新完直接读缓存
+  refresh: Mutex<()>,
 }
 
 #[derive(Debug, Clone)]
 struct CachedToken {
+  /// 获取 token 时用的密钥指纹，见 credentials_hash
+  key_hash: String,
   access_token: String,
   expires_at_unix: u64,
 }
 
+impl CachedToken {
+  /// 属于当前密钥，且距离过期还有 TOKEN_REFRESH_SAFETY_WINDOW 以上（提前刷新，避免临界点失败）
+  fn is_usable(&self, key_hash: &str) -> bool {
+    let safe_expire = self
+      .expires_at_unix
+      .saturating_sub(TOKEN_REFRESH_SAFETY_WINDOW.as_secs());
+
+    self.key_hash == key_hash && unix_now() < safe_expire
+  }
+}
+
 /// ======= 前端调用参数 / 返回 =======
 
 #[derive(Debug, Deserialize)]
@@ -54,41 +100,265 @@ pub struct TextTranslatePayload {
   pub from: String, // 可传 "auto"
   pub to: String,
   pub term_ids: Option<String>,
+  /// 为 true 时跳过本地缓存强制请求接口（结果仍会写回缓存）
+  pub no_cache: Option<bool>,
+  /// 本地术语表：关联了百度术语库时合并进 term_ids，否则在客户端强制替换术语
+  #[serde(default)]
+  pub glossary_id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TextTranslateResult {
   pub from: String,
   pub to: String,
   pub dst: String, // 拼接后的译文（按段落用 \n 拼）
+  /// 逐段的原文/译文对照（百度按换行分段；不分段的服务只有一段）
+  #[serde(default)]
+  pub segments: Vec<TranslationSegment>,
   pub raw: serde_json::Value, // 保留原始响应，方便你调试
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationSegment {
+  pub src: String,
+  pub dst: String,
+}
+
+/// 百度接口的通用响应外壳：成功时有 result，失败时有 error_code / error_msg
+#[derive(Debug, Deserialize)]
+struct BaiduResponse<T> {
+  result: Option<T>,
+  error_code: Option<BaiduErrorCode>,
+  error_msg: Option<String>,
+}
+
+/// error_code 有的接口是数字，有的是字符串
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BaiduErrorCode {
+  Num(i64),
+  Str(String),
+}
+
+/// 通用文本翻译 result：{ from, to, trans_result: [{src, dst}] }
+#[derive(Debug, Deserialize)]
+struct BaiduTextResult {
+  from: String,
+  to: String,
+  #[serde(default)]
+  trans_result: Vec<TranslationSegment>,
+}
+
+/// 文档翻译查询 result：{ data: { id, status, reason, output: { files: [...] } } }
+#[derive(Debug, Deserialize)]
+struct BaiduDocQueryResult {
+  data: DocTaskInfo,
+}
+
+/// 文档翻译任务的服务端状态
+#[derive(Debug, Deserialize)]
+pub struct DocTaskInfo {
+  /// NotStarted / Running / Succeeded / Failed / Expired
+  pub status: String,
+  #[serde(default)]
+  pub reason: Option<String>,
+  #[serde(default)]
+  pub output: Option<DocTaskOutput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocTaskOutput {
+  #[serde(default)]
+  pub files: Vec<DocOutputFile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocOutputFile {
+  #[serde(default)]
+  pub format: Option<String>,
+  #[serde(default)]
+  pub filename: Option<String>,
+  /// 译文下载地址（有效期有限，要及时下载）
+  pub url: String,
+}
+
+/// 图片翻译响应：和其他接口不同，结果在 data 里而不是 result
+#[derive(Debug, Deserialize)]
+struct BaiduPicResponse {
+  data: Option<BaiduPicData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BaiduPicData {
+  #[serde(default)]
+  from: String,
+  #[serde(default)]
+  to: String,
+  #[serde(default)]
+  sum_src: String,
+  #[serde(default)]
+  sum_dst: String,
+  #[serde(default)]
+  paste_img: Option<String>,
+  #[serde(default)]
+  content: Vec<BaiduPicContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BaiduPicContent {
+  #[serde(default)]
+  src: String,
+  #[serde(default)]
+  dst: String,
+  /// "left top width height"
+  #[serde(default)]
+  rect: String,
+  #[serde(default)]
+  line_count: u32,
+  #[serde(default)]
+  paste_img: Option<String>,
+  #[serde(default)]
+  points: Vec<PicPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTranslatePayload {
+  /// 待翻译的文本列表，结果按相同顺序返回
+  pub items: Vec<String>,
+  pub from: String, // 可传 "auto"
+  pub to: String,
+  pub term_ids: Option<String>,
+  /// 每秒最多发出的请求数，默认 DEFAULT_BATCH_QPS
+  pub qps: Option<u32>,
+  pub no_cache: Option<bool>,
+  /// 前端生成的批次 ID，原样带回进度事件，用于区分同时进行的多个批次
+  pub batch_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchTranslateResult {
+  pub from: String,
+  pub to: String,
+  pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+  pub batch_id: Option<String>,
+  pub done_items: usize,
+  pub total_items: usize,
+  pub done_requests: usize,
+  pub total_requests: usize,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PicTranslatePayload {
-  /// 图片二进制（前端用 Uint8Array 传 Vec<u8>）
-  pub image: Vec<u8>,
+  /// 图片文件路径（<=4M），由后端读取
+  pub path: Option<String>,
+  /// 图片二进制（旧的调用方式，没有 path 时使用）
+  pub image: Option<Vec<u8>>,
   /// "image/png" | "image/jpeg" | "image/webp"（doc 要求小写）:contentReference[oaicite:11]{index=11}
-  pub mime: String,
+  /// 不传时按扩展名判断
+  pub mime: Option<String>,
   pub from: String,
   pub to: String,
-  /// 图片贴合类型：0/1/2:contentReference[oaicite:12]{index=12}
-  pub paste: Option<i32>,
+  /// 图片贴合类型，不传为 none:contentReference[oaicite:12]{index=12}
+  #[serde(default)]
+  pub paste: PasteMode,
+}
+
+/// 图片贴合：把译文贴回原图；前端和接口一样传 0 / 1 / 2
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u8")]
+pub enum PasteMode {
+  /// 0：不贴合，只返回文字
+  #[default]
+  None,
+  /// 1：整图贴合，返回一张贴好译文的图片
+  Full,
+  /// 2：逐段贴合，每个文本块返回一张贴好译文的小图
+  Block,
+}
+
+impl TryFrom<u8> for PasteMode {
+  type Error = String;
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(PasteMode::None),
+      1 => Ok(PasteMode::Full),
+      2 => Ok(PasteMode::Block),
+      other => Err(format!("invalid paste mode: {other}, expected 0, 1 or 2")),
+    }
+  }
+}
+
+impl PasteMode {
+  /// 接口里的 paste 参数值
+  fn as_param(self) -> &'static str {
+    match self {
+      PasteMode::None => "0",
+      PasteMode::Full => "1",
+      PasteMode::Block => "2",
+    }
+  }
 }
 
 #[derive(Debug, Serialize)]
 pub struct PicTranslateResult {
+  pub from: String,
+  pub to: String,
+  /// 识别出的全部原文 / 译文
+  pub src: String,
+  pub dst: String,
+  /// 逐块的识别结果
+  pub sections: Vec<PicSection>,
+  /// 整图贴合（paste=full）后的图片，解码后保存在临时目录
+  pub paste_image: Option<String>,
   pub raw: serde_json::Value,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PicSection {
+  pub src: String,
+  pub dst: String,
+  /// 文本块在原图中的外接矩形
+  pub rect: PicRect,
+  /// 文本块的四个顶点（文字倾斜时比 rect 更准）
+  pub points: Vec<PicPoint>,
+  pub line_count: u32,
+  /// 逐段贴合（paste=block）时这一块贴好译文的图片路径
+  pub paste_image: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PicRect {
+  pub left: i32,
+  pub top: i32,
+  pub width: i32,
+  pub height: i32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PicPoint {
+  pub x: i32,
+  pub y: i32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DocCreatePayload {
   pub from: String,
   pub to: String,
-  /// 文件二进制（<=50M，PDF 扫描件要翻图片建议 trans_image=1）:contentReference[oaicite:13]{index=13}
-  pub file: Vec<u8>,
-  /// 输入文件类型：pdf/doc/docx/xls/xlsx/ppt/pptx/txt/wps 等（你前端提示里也写了）
-  pub format: String,
+  /// 文件路径（<=50M，PDF 扫描件要翻图片建议 trans_image=1）:contentReference[oaicite:13]{index=13}
+  pub path: Option<String>,
+  /// 文件二进制（旧的调用方式，没有 path 时使用）
+  pub file: Option<Vec<u8>>,
+  /// 输入文件类型：pdf/doc/docx/xls/xlsx/ppt/pptx/txt/wps 等；不传时按扩展名判断
+  /// 会和文件内容（魔数）核对，对不上时直接报错
+  pub format: Option<String>,
+  /// 不传时用 path 里的文件名
   pub filename: Option<String>,
   /// 是否翻译文档中的图片：0/1:contentReference[oaicite:14]{index=14}
   pub trans_image: Option<i32>,
@@ -100,6 +370,8 @@ pub struct DocCreatePayload {
 #[derive(Debug, Serialize)]
 pub struct DocCreateResult {
   pub id: String,
+  /// 后台任务：之后的进度通过 DOC_PROGRESS_EVENT 事件推送
+  pub job: DocJob,
   pub raw: serde_json::Value,
 }
 
@@ -118,131 +390,92 @@ pub struct DocQueryResult {
 #[tauri::command]
 pub async fn baidu_text_translate(
   pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
   token_state: State<'_, BaiduTokenState>,
   payload: TextTranslatePayload,
 ) -> Result<TextTranslateResult, AppError> {
-  let client = build_client(get_network_opt(&pool)?)?;
-  let token = get_access_token(&pool, &client, &token_state).await?;
-
-  let url = format!("{BAIDU_TEXTTRANS_URL}?access_token={}", token);
-
-  let mut body = json!({
-    "q": payload.q,
-    "from": payload.from,
-    "to": payload.to,
-  });
-  if let Some(term_ids) = payload.term_ids {
-    body["termIds"] = json!(term_ids);
-  }
-
-  let resp_json: serde_json::Value = client
-    .post(url)
-    .header("Content-Type", "application/json;charset=utf-8")
-    .json(&body)
-    .send()
-    .await
-    .map_err(AppError::from)?
-    .json()
-    .await
-    .map_err(AppError::from)?;
-
-  // 通用版返回结构：result.trans_result[{dst,src}]:contentReference[oaicite:16]{index=16}
-  let from = resp_json
-    .pointer("/result/from")
-    .and_then(|v| v.as_str())
-    .unwrap_or("")
-    .to_string();
-  let to = resp_json
-    .pointer("/result/to")
-    .and_then(|v| v.as_str())
-    .unwrap_or("")
-    .to_string();
-
-  let dst_joined = resp_json
-    .pointer("/result/trans_result")
-    .and_then(|v| v.as_array())
-    .map(|arr| {
-      arr.iter()
-        .filter_map(|x| x.get("dst").and_then(|v| v.as_str()).map(|s| s.to_string()))
-        .collect::<Vec<_>>()
-        .join("\n")
-    })
-    .unwrap_or_default();
+  // 术语表处理和其他 provider 共用 translate::translate
+  let ctx = TranslateContext {
+    pool: &pool,
+    http: &http,
+    baidu_token: &token_state,
+  };
+  let req = TranslateRequest {
+    q: payload.q,
+    from: payload.from,
+    to: payload.to,
+    glossary_id: payload.glossary_id,
+    term_ids: payload.term_ids,
+    no_cache: payload.no_cache.unwrap_or(false),
+  };
+  let result = crate::translate::translate(&ctx, ProviderKind::Baidu, &req).await?;
+
+  crate::history::record_quietly(&pool, ProviderKind::Baidu.as_str(), &req.q, &result);
+  Ok(result)
+}
 
-  Ok(TextTranslateResult {
-    from,
-    to,
-    dst: dst_joined,
-    raw: resp_json,
-  })
+/// 批量文本翻译：多条文本按长度上限打包成若干请求，按 QPS 节流依次发送，
+/// 每完成一个请求发一次 BATCH_PROGRESS_EVENT 进度事件（批量结果不写入历史记录）
+#[tauri::command]
+pub async fn baidu_text_translate_batch(
+  app: AppHandle,
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: BatchTranslatePayload,
+) -> Result<BatchTranslateResult, AppError> {
+  let client = http.client(&pool, ProxyService::Baidu).await?;
+  translate_batch(&app, &pool, &client, &token_state, &payload).await
 }
 
 #[tauri::command]
 pub async fn baidu_pic_translate(
   pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
   token_state: State<'_, BaiduTokenState>,
-  payload: PicTranslatePayload,
+  mut payload: PicTranslatePayload,
 ) -> Result<PicTranslateResult, AppError> {
-  let client = build_client(get_network_opt(&pool)?)?;
-  let token = get_access_token(&pool, &client, &token_state).await?;
-
-  let url = format!("{BAIDU_PICTRANS_URL}?access_token={}", token);
-
-  // multipart 字段：image/from/to/v(固定3)/paste:contentReference[oaicite:17]{index=17}
-  let file_part = multipart::Part::bytes(payload.image)
-    .file_name("image")
-    .mime_str(&payload.mime)
-    .map_err(AppError::from)?;
-
-  let mut form = multipart::Form::new()
-    .part("image", file_part)
-    .text("from", payload.from)
-    .text("to", payload.to)
-    .text("v", "3"); // 固定值 3:contentReference[oaicite:18]{index=18}
-
-  if let Some(paste) = payload.paste {
-    form = form.text("paste", paste.to_string());
-  }
+  // 先读文件并校验，不合格的文件不发请求
+  let source = UploadSource::new(payload.path.take(), payload.image.take(), None)?;
+  let image = upload::read_image(source, payload.mime.as_deref()).await?;
+  let client = http.client(&pool, ProxyService::Baidu).await?;
 
-  let resp_json: serde_json::Value = client
-    .post(url)
-    .multipart(form)
-    .send()
-    .await
-    .map_err(AppError::from)?
-    .json()
-    .await
-    .map_err(AppError::from)?;
-
-  Ok(PicTranslateResult { raw: resp_json })
+  pic_translate(&pool, &client, &token_state, &image, &payload.from, &payload.to, payload.paste).await
 }
 
+/// 创建文档翻译任务，并交给后台轮询：完成后自动下载到输出目录（关掉页面也不影响）
 #[tauri::command]
 pub async fn baidu_doc_translate_create(
+  app: AppHandle,
   pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
   token_state: State<'_, BaiduTokenState>,
-  payload: DocCreatePayload,
+  mut payload: DocCreatePayload,
 ) -> Result<DocCreateResult, AppError> {
-  let client = build_client(get_network_opt(&pool)?)?;
-  let token = get_access_token(&pool, &client, &token_state).await?;
-
-  let url = format!("{BAIDU_DOC_CREATE_URL}?access_token={}", token);
+  // 先读文件并校验，不合格的文件不发请求
+  let source = UploadSource::new(payload.path.take(), payload.file.take(), payload.filename.clone())?;
+  let doc = upload::read_doc(
+    source,
+    payload.format.as_deref(),
+    payload.output_format.as_deref(),
+  )
+  .await?;
+  let filename = payload.filename.clone().unwrap_or_else(|| doc.filename.clone());
+  let client = http.client(&pool, ProxyService::Baidu).await?;
 
   // create 接口 input.content 是 base64:contentReference[oaicite:19]{index=19}
-  let content_b64 = general_purpose::STANDARD.encode(payload.file);
+  let content_b64 = general_purpose::STANDARD.encode(&doc.bytes);
 
   let mut req_body = json!({
     "from": payload.from,
     "to": payload.to,
     "input": {
       "content": content_b64,
-      "format": payload.format,
+      "format": doc.format,
+      "filename": filename,
     }
   });
 
-  if let Some(name) = payload.filename {
-    req_body["input"]["filename"] = json!(name);
-  }
   if let Some(trans_image) = payload.trans_image {
     req_body["input"]["trans_image"] = json!(trans_image);
   }
@@ -250,24 +483,15 @@ pub async fn baidu_doc_translate_create(
   // output.formats / filename_prefix:contentReference[oaicite:20]{index=20}
   if payload.output_format.is_some() || payload.filename_prefix.is_some() {
     req_body["output"] = json!({});
-    if let Some(fmt) = payload.output_format {
+    if let Some(fmt) = &payload.output_format {
       req_body["output"]["formats"] = json!([fmt]);
     }
-    if let Some(prefix) = payload.filename_prefix {
+    if let Some(prefix) = &payload.filename_prefix {
       req_body["output"]["filename_prefix"] = json!(prefix);
     }
   }
 
-  let resp_json: serde_json::Value = client
-    .post(url)
-    .header("Content-Type", "application/json;charset=utf-8")
-    .json(&req_body)
-    .send()
-    .await
-    .map_err(AppError::from)?
-    .json()
-    .await
-    .map_err(AppError::from)?;
+  let resp_json = post_json(&pool, &client, &token_state, BAIDU_DOC_CREATE_URL, &req_body).await?;
 
   // 返回 result.id（任务ID）:contentReference[oaicite:21]{index=21}
   let id = resp_json
@@ -281,50 +505,379 @@ pub async fn baidu_doc_translate_create(
     return Err(AppError::msg(format!("Baidu doc create failed: {}", resp_json)));
   }
 
-  Ok(DocCreateResult { id, raw: resp_json })
+  let job = DocJob::new(
+    id.clone(),
+    &payload.from,
+    &payload.to,
+    Some(filename),
+    &doc.format,
+    payload.output_format.clone(),
+  );
+  crate::doc_job::insert(&pool, &job)?;
+  crate::doc_job::spawn(&app, id.clone());
+
+  Ok(DocCreateResult {
+    id,
+    job,
+    raw: resp_json,
+  })
 }
 
 #[tauri::command]
 pub async fn baidu_doc_translate_query(
   pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
   token_state: State<'_, BaiduTokenState>,
   payload: DocQueryPayload,
 ) -> Result<DocQueryResult, AppError> {
-  let client = build_client(get_network_opt(&pool)?)?;
-  let token = get_access_token(&pool, &client, &token_state).await?;
-
-  let url = format!("{BAIDU_DOC_QUERY_URL}?access_token={}", token);
+  let client = http.client(&pool, ProxyService::Baidu).await?;
 
   // query body: { id }:contentReference[oaicite:22]{index=22}
   let req_body = json!({ "id": payload.id });
 
-  let resp_json: serde_json::Value = client
-    .post(url)
-    .header("Content-Type", "application/json;charset=utf-8")
-    .json(&req_body)
-    .send()
+  let resp_json = post_json(&pool, &client, &token_state, BAIDU_DOC_QUERY_URL, &req_body).await?;
+
+  Ok(DocQueryResult { raw: resp_json })
+}
+
+/// 查询文档翻译任务状态（后台轮询用）
+/// endpoint 一般是 BAIDU_DOC_QUERY_URL，单独传入方便测试时指向本地 mock server
+pub(crate) async fn query_doc_task(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  endpoint: &str,
+  id: &str,
+) -> Result<DocTaskInfo, AppError> {
+  let req_body = json!({ "id": id });
+  let resp_json = post_json(pool, client, token_state, endpoint, &req_body).await?;
+
+  parse_baidu_result::<BaiduDocQueryResult>(&resp_json).map(|r| r.data)
+}
+
+/// 图片翻译主流程（剪贴板翻译也复用它）
+pub(crate) async fn pic_translate(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  image: &UploadFile,
+  from: &str,
+  to: &str,
+  paste: PasteMode,
+) -> Result<PicTranslateResult, AppError> {
+  let resp_json = with_access_token(pool, client, token_state, |token| async move {
+    let url = format!("{BAIDU_PICTRANS_URL}?access_token={}", token);
+
+    // multipart 字段：image/from/to/v(固定3)/paste:contentReference[oaicite:17]{index=17}
+    // form 发送后就被消耗，重试时要重新构建
+    let file_part = multipart::Part::bytes(image.bytes.clone())
+      .file_name(image.filename.clone())
+      .mime_str(&image.format)
+      .map_err(AppError::from)?;
+
+    let form = multipart::Form::new()
+      .part("image", file_part)
+      .text("from", from.to_string())
+      .text("to", to.to_string())
+      .text("v", "3") // 固定值 3:contentReference[oaicite:18]{index=18}
+      .text("paste", paste.as_param());
+
+    let resp_json: serde_json::Value = client
+      .post(url)
+      .multipart(form)
+      .send()
+      .await
+      .map_err(AppError::from)?
+      .json()
+      .await
+      .map_err(AppError::from)?;
+
+    check_baidu_error(&resp_json)?;
+    Ok(resp_json)
+  })
+  .await?;
+
+  let (mut result, paste_images) = parse_pic_response(resp_json, paste)?;
+
+  for (section, b64) in paste_images {
+    let path = save_paste_image(&b64).await?;
+    match section {
+      Some(i) => result.sections[i].paste_image = Some(path),
+      None => result.paste_image = Some(path),
+    }
+  }
+
+  Ok(result)
+}
+
+/// 待保存的贴合图片：(所属文本块下标，整图为 None；base64)
+type PendingPasteImage = (Option<usize>, String);
+
+/// 把接口响应整理成 PicTranslateResult
+///
+/// 贴合图片（base64）不放进结果，单独返回待保存的列表；
+/// raw 里也去掉 pasteImg，避免几 MB 的 base64 再走一遍 IPC
+fn parse_pic_response(
+  mut resp_json: serde_json::Value,
+  paste: PasteMode,
+) -> Result<(PicTranslateResult, Vec<PendingPasteImage>), AppError> {
+  let data = serde_json::from_value::<BaiduPicResponse>(resp_json.clone())?
+    .data
+    .ok_or_else(|| AppError::msg(format!("Baidu pic response has no data: {resp_json}")))?;
+  strip_paste_img(&mut resp_json);
+
+  let mut paste_images = Vec::new();
+  if let (PasteMode::Full, Some(b64)) = (paste, data.paste_img) {
+    paste_images.push((None, b64));
+  }
+
+  let mut sections = Vec::with_capacity(data.content.len());
+  for (i, c) in data.content.into_iter().enumerate() {
+    if let (PasteMode::Block, Some(b64)) = (paste, c.paste_img) {
+      paste_images.push((Some(i), b64));
+    }
+    sections.push(PicSection {
+      rect: parse_rect(&c.rect),
+      src: c.src,
+      dst: c.dst,
+      points: c.points,
+      line_count: c.line_count,
+      paste_image: None,
+    });
+  }
+
+  let result = PicTranslateResult {
+    from: data.from,
+    to: data.to,
+    src: data.sum_src,
+    dst: data.sum_dst,
+    sections,
+    paste_image: None,
+    raw: resp_json,
+  };
+  Ok((result, paste_images))
+}
+
+/// 去掉 data.pasteImg 和 data.content[].pasteImg
+fn strip_paste_img(resp_json: &mut serde_json::Value) {
+  let Some(data) = resp_json.get_mut("data").and_then(|d| d.as_object_mut()) else {
+    return;
+  };
+  data.remove("pasteImg");
+  if let Some(content) = data.get_mut("content").and_then(|c| c.as_array_mut()) {
+    for c in content.iter_mut().filter_map(|c| c.as_object_mut()) {
+      c.remove("pasteImg");
+    }
+  }
+}
+
+/// "79 23 111 73" -> PicRect；格式不对时返回全 0
+fn parse_rect(rect: &str) -> PicRect {
+  let nums: Vec<i32> = rect
+    .split_whitespace()
+    .filter_map(|n| n.parse().ok())
+    .collect();
+  match nums[..] {
+    [left, top, width, height] => PicRect {
+      left,
+      top,
+      width,
+      height,
+    },
+    _ => PicRect::default(),
+  }
+}
+
+/// 贴合后的图片（base64）解码保存到临时目录，返回文件路径
+///
+/// 文件名取内容哈希，同一张图重复翻译不会堆积文件；顺带清理超过 PIC_TEMP_TTL 的旧文件
+async fn save_paste_image(b64: &str) -> Result<String, AppError> {
+  let bytes = general_purpose::STANDARD
+    .decode(b64.trim())
+    .map_err(|e| AppError::Serde(format!("decode pasteImg failed: {e}")))?;
+
+  let ext = if bytes.starts_with(b"\x89PNG") { "png" } else { "jpg" };
+  let hash = format!("{:x}", Sha256::digest(&bytes));
+  let dir = std::env::temp_dir().join(PIC_TEMP_DIR);
+  tokio::fs::create_dir_all(&dir)
     .await
-    .map_err(AppError::from)?
-    .json()
+    .map_err(|e| AppError::Io(format!("create dir failed: {e}")))?;
+  remove_stale_files(&dir, PIC_TEMP_TTL).await;
+
+  let path = dir.join(format!("{}.{ext}", &hash[..16]));
+  tokio::fs::write(&path, &bytes)
     .await
-    .map_err(AppError::from)?;
+    .map_err(|e| AppError::Io(format!("write {} failed: {e}", path.display())))?;
 
-  Ok(DocQueryResult { raw: resp_json })
+  Ok(path.to_string_lossy().into_owned())
 }
 
-/// ======= token + client helpers =======
+/// 删除目录里修改时间早于 ttl 的文件；清理失败不影响本次保存
+async fn remove_stale_files(dir: &std::path::Path, ttl: Duration) {
+  let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+    return;
+  };
+  let now = SystemTime::now();
+  while let Ok(Some(entry)) = entries.next_entry().await {
+    let Ok(meta) = entry.metadata().await else {
+      continue;
+    };
+    let stale = meta
+      .modified()
+      .ok()
+      .and_then(|t| now.duration_since(t).ok())
+      .is_some_and(|age| age > ttl);
+    if meta.is_file() && stale {
+      let _ = tokio::fs::remove_file(entry.path()).await;
+    }
+  }
+}
+
+// ======= token + client helpers =======
 
-async fn get_access_token(
+/// 批量翻译主流程（i18n 资源文件翻译也复用它）
+pub(crate) async fn translate_batch(
+  app: &AppHandle,
   pool: &DbPool,
   client: &Client,
   token_state: &BaiduTokenState,
-) -> Result<String, AppError> {
-  // 1) 先读缓存
-  if let Some(tok) = try_get_cached_token(token_state).await {
-    return Ok(tok);
+  payload: &BatchTranslatePayload,
+) -> Result<BatchTranslateResult, AppError> {
+  let mut plan = BatchPlan::new(&payload.items, BAIDU_TEXT_MAX_BYTES)?;
+  let total_requests = plan.chunks.len();
+  let interval = Duration::from_secs(1) / payload.qps.unwrap_or(DEFAULT_BATCH_QPS).max(1);
+
+  let mut from = payload.from.clone();
+  let mut to = payload.to.clone();
+  let mut next_at = tokio::time::Instant::now();
+
+  for idx in 0..total_requests {
+    let request = TextTranslatePayload {
+      q: plan.chunks[idx].text.clone(),
+      from: payload.from.clone(),
+      to: payload.to.clone(),
+      term_ids: payload.term_ids.clone(),
+      no_cache: payload.no_cache,
+      glossary_id: None,
+    };
+
+    let mut retries = 0;
+    let result = loop {
+      tokio::time::sleep_until(next_at).await;
+      next_at = tokio::time::Instant::now() + interval;
+
+      match text_translate(pool, client, token_state, &request).await {
+        Err(AppError::RateLimited(detail)) if retries < BATCH_RATE_LIMIT_RETRIES => {
+          retries += 1;
+          log::warn!("baidu batch translate rate limited (retry {retries}): {detail}");
+          // 被限流：在正常间隔之外再指数退避
+          next_at += interval * 2u32.pow(retries);
+        }
+        other => break other?,
+      }
+    };
+
+    // from=auto 时以第一块的检测结果为准
+    if idx == 0 {
+      from = result.from;
+      to = result.to;
+    }
+    plan.fill(idx, result.segments.into_iter().map(|s| s.dst).collect())?;
+
+    let _ = app.emit(
+      BATCH_PROGRESS_EVENT,
+      BatchProgress {
+        batch_id: payload.batch_id.clone(),
+        done_items: plan.done_items_after(idx),
+        total_items: plan.total_items(),
+        done_requests: idx + 1,
+        total_requests,
+      },
+    );
+  }
+
+  Ok(BatchTranslateResult {
+    from,
+    to,
+    items: plan.into_results(),
+  })
+}
+
+pub(crate) async fn text_translate(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  payload: &TextTranslatePayload,
+) -> Result<TextTranslateResult, AppError> {
+  text_translate_at(pool, client, token_state, BAIDU_TEXTTRANS_URL, payload).await
+}
+
+/// endpoint 单独传入，方便测试时指向本地 mock server
+pub(crate) async fn text_translate_at(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  endpoint: &str,
+  payload: &TextTranslatePayload,
+) -> Result<TextTranslateResult, AppError> {
+  let cache_key = CacheKey {
+    provider: ProviderKind::Baidu.as_str(),
+    from: &payload.from,
+    to: &payload.to,
+    text: &payload.q,
+    term_ids: payload.term_ids.as_deref(),
+  };
+
+  // 缓存读写失败不影响翻译本身
+  if !payload.no_cache.unwrap_or(false) {
+    match cache::get(pool, &cache_key) {
+      Ok(Some(hit)) => return Ok(hit),
+      Ok(None) => {}
+      Err(e) => log::warn!("read translation cache failed: {e}"),
+    }
+  }
+
+  let mut body = json!({
+    "q": payload.q,
+    "from": payload.from,
+    "to": payload.to,
+  });
+  if let Some(term_ids) = &payload.term_ids {
+    body["termIds"] = json!(term_ids);
+  }
+
+  let resp_json = post_json(pool, client, token_state, endpoint, &body).await?;
+
+  // 通用版返回结构：result.trans_result[{dst,src}]:contentReference[oaicite:16]{index=16}
+  let parsed: BaiduTextResult = parse_baidu_result(&resp_json)?;
+
+  let result = TextTranslateResult {
+    from: parsed.from,
+    to: parsed.to,
+    dst: parsed
+      .trans_result
+      .iter()
+      .map(|s| s.dst.as_str())
+      .collect::<Vec<_>>()
+      .join("\n"),
+    segments: parsed.trans_result,
+    raw: resp_json,
+  };
+
+  if let Err(e) = cache::put(pool, &cache_key, &result) {
+    log::warn!("write translation cache failed: {e}");
   }
 
-  // 2) 缓存失效 -> 拉新 token
+  Ok(result)
+}
+
+/// oauth_url 单独传入，方便测试时指向本地 mock server
+async fn fetch_access_token(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  oauth_url: &str,
+) -> Result<String, AppError> {
   let keys = get_api_keys_required(pool)?;
 
   let client_id = keys.translation.baidu.api_key.clone();
@@ -334,8 +887,22 @@ async fn get_access_token(
     return Err(AppError::msg("Baidu API Key/Secret 为空，请先在设置里填写"));
   }
 
+  let key_hash = credentials_hash(&client_id, &client_secret);
+
+  // 1) 先读缓存（启动时已从 SQLite 恢复）
+  if let Some(tok) = try_get_cached_token(token_state, &key_hash).await {
+    return Ok(tok);
+  }
+
+  // 2) 缓存失效 / 密钥变了 -> 拉新 token
+  // 拿到刷新锁后再查一次：排队期间可能已经有别的请求刷新好了
+  let _refreshing = token_state.refresh.lock().await;
+  if let Some(tok) = try_get_cached_token(token_state, &key_hash).await {
+    return Ok(tok);
+  }
+
   let url = format!(
-    "{BAIDU_OAUTH_URL}?grant_type=client_credentials&client_id={}&client_secret={}",
+    "{oauth_url}?grant_type=client_credentials&client_id={}&client_secret={}",
     urlencoding::encode(&client_id),
     urlencoding::encode(&client_secret)
   );
@@ -365,34 +932,216 @@ async fn get_access_token(
     return Err(AppError::msg(format!("Baidu oauth failed: {}", resp_json)));
   }
 
-  let now = unix_now();
-  let expires_at = now + expires_in;
+  let token = CachedToken {
+    key_hash,
+    access_token: access_token.clone(),
+    expires_at_unix: unix_now() + expires_in,
+  };
 
-  {
-    let mut guard = token_state.inner.lock().await;
-    *guard = Some(CachedToken {
-      access_token: access_token.clone(),
-      expires_at_unix: expires_at,
-    });
+  // 持久化失败只影响下次启动，不影响本次请求
+  if let Err(e) = persist_token(pool, &token) {
+    log::warn!("persist baidu access token failed: {e}");
   }
 
+  *token_state.inner.lock().await = Some(token);
+
   Ok(access_token)
 }
 
-async fn try_get_cached_token(token_state: &BaiduTokenState) -> Option<String> {
+async fn try_get_cached_token(token_state: &BaiduTokenState, key_hash: &str) -> Option<String> {
   let guard = token_state.inner.lock().await;
   let tok = guard.as_ref()?;
 
-  let now = unix_now();
-  // 提前 TOKEN_REFRESH_SAFETY_WINDOW 刷新，避免临界点失败
-  let safe_expire = tok
-    .expires_at_unix
-    .saturating_sub(TOKEN_REFRESH_SAFETY_WINDOW.as_secs());
+  tok.is_usable(key_hash).then(|| tok.access_token.clone())
+}
+
+/// token 和哪组密钥绑定：sha256(api_key \0 app_secret)，不直接落库明文密钥
+fn credentials_hash(api_key: &str, app_secret: &str) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(api_key);
+  hasher.update([0u8]);
+  hasher.update(app_secret);
+  format!("{:x}", hasher.finalize())
+}
+
+// ======= token 持久化（SQLite，表里最多一行）=======
+
+fn load_persisted_token(pool: &DbPool) -> Result<Option<CachedToken>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let row: Option<(String, String, i64)> = conn
+    .query_row(
+      "SELECT key_hash, access_token, expires_at_unix FROM baidu_access_token LIMIT 1",
+      [],
+      |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )
+    .optional()
+    .map_err(|e| AppError::Db(format!("query baidu token failed: {e}")))?;
+
+  let Some((key_hash, access_token, expires_at_unix)) = row else {
+    return Ok(None);
+  };
+
+  Ok(Some(CachedToken {
+    key_hash,
+    access_token: crate::crypto::decrypt(&access_token)?,
+    expires_at_unix: expires_at_unix.max(0) as u64,
+  }))
+}
+
+fn persist_token(pool: &DbPool, token: &CachedToken) -> Result<(), AppError> {
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+
+  tx.execute("DELETE FROM baidu_access_token", [])
+    .map_err(|e| AppError::Db(format!("delete baidu token failed: {e}")))?;
+  tx.execute(
+    r#"
+    INSERT INTO baidu_access_token(key_hash, access_token, expires_at_unix, updated_at)
+    VALUES (?1, ?2, ?3, ?4)
+    "#,
+    params![
+      token.key_hash,
+      crate::crypto::encrypt(&token.access_token)?,
+      token.expires_at_unix as i64,
+      unix_now() as i64
+    ],
+  )
+  .map_err(|e| AppError::Db(format!("insert baidu token failed: {e}")))?;
+
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(())
+}
+
+fn delete_persisted_token(pool: &DbPool) -> Result<(), AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute("DELETE FROM baidu_access_token", [])
+    .map_err(|e| AppError::Db(format!("delete baidu token failed: {e}")))?;
+
+  Ok(())
+}
+
+/// 带 access_token 调用百度接口：返回 token 无效（110/111）时丢弃缓存、重新获取 token 再试一次
+async fn with_access_token<T, F, Fut>(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  call: F,
+) -> Result<T, AppError>
+where
+  F: Fn(String) -> Fut,
+  Fut: Future<Output = Result<T, AppError>>,
+{
+  with_access_token_at(pool, client, token_state, BAIDU_OAUTH_URL, call).await
+}
+
+/// oauth_url 单独传入，方便测试时指向本地 mock server
+async fn with_access_token_at<T, F, Fut>(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  oauth_url: &str,
+  call: F,
+) -> Result<T, AppError>
+where
+  F: Fn(String) -> Fut,
+  Fut: Future<Output = Result<T, AppError>>,
+{
+  let token = fetch_access_token(pool, client, token_state, oauth_url).await?;
+
+  match call(token.clone()).await {
+    Err(AppError::TokenInvalid(detail)) => {
+      log::warn!("baidu access token rejected, refreshing: {detail}");
+      // 只丢弃被拒的这个 token：并发请求可能已经换上了新的，不能再清掉
+      token_state.invalidate(pool, Some(&token)).await;
+
+      let token = fetch_access_token(pool, client, token_state, oauth_url).await?;
+      call(token).await
+    }
+    other => other,
+  }
+}
+
+/// POST JSON 到百度接口并检查 error_code（含 token 失效重试）
+async fn post_json(
+  pool: &DbPool,
+  client: &Client,
+  token_state: &BaiduTokenState,
+  endpoint: &str,
+  body: &serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+  with_access_token(pool, client, token_state, |token| async move {
+    let resp_json: serde_json::Value = client
+      .post(format!("{endpoint}?access_token={token}"))
+      .header("Content-Type", "application/json;charset=utf-8")
+      .json(body)
+      .send()
+      .await
+      .map_err(AppError::from)?
+      .json()
+      .await
+      .map_err(AppError::from)?;
+
+    check_baidu_error(&resp_json)?;
+    Ok(resp_json)
+  })
+  .await
+}
+
+/// 把百度响应拆成 result；带 error_code 的错误体按类型映射成 AppError
+fn parse_baidu_result<T: serde::de::DeserializeOwned>(resp_json: &serde_json::Value) -> Result<T, AppError> {
+  check_baidu_error(resp_json)?;
+
+  let resp: BaiduResponse<T> = serde_json::from_value(resp_json.clone())?;
+  resp
+    .result
+    .ok_or_else(|| AppError::msg(format!("Baidu response has no result: {resp_json}")))
+}
+
+/// 响应里带非 0 的 error_code 时转成 AppError（图片/文档翻译成功时也会返回 error_code = 0）
+fn check_baidu_error(resp_json: &serde_json::Value) -> Result<(), AppError> {
+  let resp: BaiduResponse<serde::de::IgnoredAny> = serde_json::from_value(resp_json.clone())?;
 
-  if now < safe_expire {
-    Some(tok.access_token.clone())
-  } else {
-    None
+  match resp.error_code {
+    Some(BaiduErrorCode::Num(0)) | None => Ok(()),
+    Some(BaiduErrorCode::Str(s)) if s == "0" || s.is_empty() => Ok(()),
+    Some(code) => Err(baidu_error(code, resp.error_msg.unwrap_or_default())),
+  }
+}
+
+/// 百度错误码 -> AppError
+/// - 110/111：access token 无效 / 过期
+/// - 18/54003/54005：QPS 或频率限制
+/// - 4/17/19/54004：请求总量 / 日配额 / 余额不足
+/// - 58001：语种方向不支持
+/// - 58002：服务已关闭（控制台里没开通或被停用）
+fn baidu_error(code: BaiduErrorCode, msg: String) -> AppError {
+  let code = match code {
+    BaiduErrorCode::Num(n) => n.to_string(),
+    BaiduErrorCode::Str(s) => s,
+  };
+  let detail = format!("{msg} ({code})");
+
+  match code.as_str() {
+    "110" | "111" => AppError::TokenInvalid(detail),
+    "18" | "54003" | "54005" => AppError::RateLimited(detail),
+    "4" | "17" | "19" | "54004" => AppError::QuotaExhausted(detail),
+    "58001" => AppError::UnsupportedLanguage(detail),
+    "58002" => AppError::ServiceDisabled(detail),
+    _ => AppError::Provider { code, msg },
   }
 }
 
@@ -408,50 +1157,451 @@ fn get_api_keys_required(pool: &DbPool) -> Result<ApiKeysForm, AppError> {
   keys_opt.ok_or_else(|| AppError::msg("未配置 API Keys，请先在设置中保存"))
 }
 
-fn get_network_opt(pool: &DbPool) -> Result<Option<NetworkProxyForm>, AppError> {
-  crate::settings::get_network(pool)
-}
+impl BaiduTokenState {
+  /// 启动时从 SQLite 恢复上次的 token（读取失败就当没有，首次请求时再获取）
+  pub fn load(pool: &DbPool) -> Self {
+    let token = load_persisted_token(pool).unwrap_or_else(|e| {
+      log::warn!("load baidu access token failed: {e}");
+      None
+    });
 
-fn build_client(proxy: Option<NetworkProxyForm>) -> Result<Client, AppError> {
-  let mut builder = reqwest::ClientBuilder::new();
+    Self {
+      inner: Mutex::new(token),
+      refresh: Mutex::new(()),
+    }
+  }
 
-  if let Some(p) = proxy {
-    match p.mode {
-      ProxyMode::Disable => {}
-      ProxyMode::System => {
-        // reqwest 默认会读系统代理（按平台）
-      }
-      ProxyMode::Manual => {
-        let host = p.host.unwrap_or_default();
-        let port = p.port.unwrap_or(0);
-        let proto = p.protocol.unwrap_or(ProxyProtocol::Http);
-        if !host.is_empty() && port != 0 {
-          let scheme = match proto {
-            ProxyProtocol::Http => "http",
-            ProxyProtocol::Https => "https",
-            ProxyProtocol::Socks5 => "socks5",
-          };
-          let proxy_url = format!("{scheme}://{host}:{port}");
-          let mut px = reqwest::Proxy::all(&proxy_url).map_err(AppError::from)?;
-          if let (Some(u), Some(pw)) = (p.username, p.password) {
-            if !u.is_empty() {
-              px = px.basic_auth(&u, &pw);
-            }
-          }
-          builder = builder.proxy(px);
-        }
+  /// 丢弃缓存的 token（内存 + SQLite）：密钥变更或接口返回 token 无效时调用
+  /// - rejected 为 None：无条件丢弃（密钥变更）
+  /// - rejected 为 Some：只有缓存里还是这个被拒的 token 时才丢弃，别的请求已经刷新过就保留新 token
+  ///
+  /// 先拿刷新锁，保证不会在刷新过程中把刚持久化的新 token 删掉
+  pub async fn invalidate(&self, pool: &DbPool, rejected: Option<&str>) {
+    let _refreshing = self.refresh.lock().await;
+    let mut inner = self.inner.lock().await;
+
+    if let Some(rejected) = rejected {
+      if inner.as_ref().map(|t| t.access_token.as_str()) != Some(rejected) {
+        return;
       }
     }
+    *inner = None;
+
+    if let Err(e) = delete_persisted_token(pool) {
+      log::warn!("delete baidu access token failed: {e}");
+    }
   }
+}
 
-  builder.build().map_err(AppError::from)
+/// 空状态：没有缓存的 token
+#[cfg(test)]
+impl BaiduTokenState {
+  /// 预先放入一个属于给定密钥的有效 token，请求时不会再去调 OAuth
+  pub(crate) fn with_token(api_key: &str, app_secret: &str, access_token: &str) -> Self {
+    Self {
+      inner: Mutex::new(Some(CachedToken {
+        key_hash: credentials_hash(api_key, app_secret),
+        access_token: access_token.to_string(),
+        expires_at_unix: unix_now() + 2592000,
+      })),
+      refresh: Mutex::new(()),
+    }
+  }
 }
 
-/// 供 lib.rs 初始化 state 用
 impl Default for BaiduTokenState {
   fn default() -> Self {
     Self {
       inner: Mutex::new(None),
+      refresh: Mutex::new(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use r2d2_sqlite::SqliteConnectionManager;
+  use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+  };
+
+  /// 内存库只能有一个连接（每个连接都是独立的数据库）；密钥按明文写入，decrypt 会原样返回
+  fn pool_with_baidu_keys() -> DbPool {
+    // 获取到的 token 会加密落库
+    crate::crypto::init_for_test();
+
+    let pool = r2d2::Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory())
+      .unwrap();
+    let mut conn = pool.get().unwrap();
+    crate::db::migrate::migrate(&mut conn).unwrap();
+
+    let mut keys = ApiKeysForm::default();
+    keys.translation.baidu.api_key = "ak".to_string();
+    keys.translation.baidu.app_secret = "sk".to_string();
+    conn
+      .execute(
+        "INSERT INTO app_settings(key, value, updated_at) VALUES ('api_keys', ?1, 0)",
+        params![serde_json::to_string(&keys).unwrap()],
+      )
+      .unwrap();
+
+    drop(conn);
+    pool
+  }
+
+  #[tokio::test]
+  async fn concurrent_callers_share_one_oauth_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .and(path("/oauth/2.0/token"))
+      .and(query_param("client_id", "ak"))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(json!({ "access_token": "tok", "expires_in": 2592000 }))
+          // 让请求“在途”一段时间，其余调用方此时都在等刷新锁
+          .set_delay(Duration::from_millis(200)),
+      )
+      .expect(1)
+      .mount(&server)
+      .await;
+
+    let pool = pool_with_baidu_keys();
+    let client = Client::new();
+    let state = BaiduTokenState::default();
+    let oauth_url = format!("{}/oauth/2.0/token", server.uri());
+
+    let tokens = futures::future::join_all(
+      (0..5).map(|_| fetch_access_token(&pool, &client, &state, &oauth_url)),
+    )
+    .await;
+
+    for token in tokens {
+      assert_eq!(token.unwrap(), "tok");
+    }
+  }
+
+  #[tokio::test]
+  async fn rejected_token_is_refreshed_and_retried_once() {
+    let server = MockServer::start().await;
+    for tok in ["old", "new"] {
+      Mock::given(method("GET"))
+        .and(path("/oauth/2.0/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "access_token": tok, "expires_in": 2592000 })))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    }
+    Mock::given(method("POST"))
+      .and(path("/api"))
+      .and(query_param("access_token", "old"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "error_code": 110, "error_msg": "Access token invalid" })))
+      .mount(&server)
+      .await;
+    Mock::given(method("POST"))
+      .and(path("/api"))
+      .and(query_param("access_token", "new"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "result": "ok" })))
+      .mount(&server)
+      .await;
+
+    let pool = pool_with_baidu_keys();
+    let client = Client::new();
+    let state = BaiduTokenState::default();
+    let oauth_url = format!("{}/oauth/2.0/token", server.uri());
+    let endpoint = format!("{}/api", server.uri());
+
+    let resp = with_access_token_at(&pool, &client, &state, &oauth_url, |token| {
+      let client = &client;
+      let endpoint = &endpoint;
+      async move {
+        let resp_json: serde_json::Value = client
+          .post(format!("{endpoint}?access_token={token}"))
+          .send()
+          .await?
+          .json()
+          .await?;
+        check_baidu_error(&resp_json)?;
+        Ok(resp_json)
+      }
+    })
+    .await
+    .unwrap();
+    assert_eq!(resp["result"], "ok");
+
+    // 两次 OAuth（首次获取 + 被拒后刷新），接口只重试一次
+    let requests = server.received_requests().await.unwrap();
+    let count = |p: &str| requests.iter().filter(|r| r.url.path() == p).count();
+    assert_eq!(count("/oauth/2.0/token"), 2);
+    assert_eq!(count("/api"), 2);
+  }
+
+  #[tokio::test]
+  async fn invalidate_keeps_token_refreshed_by_another_caller() {
+    let pool = pool_with_baidu_keys();
+    async fn cached(state: &BaiduTokenState) -> Option<String> {
+      state.inner.lock().await.as_ref().map(|t| t.access_token.clone())
+    }
+
+    // 别的请求已经把被拒的 old 换成了 new：不能再清掉
+    let state = BaiduTokenState::with_token("ak", "sk", "new");
+    state.invalidate(&pool, Some("old")).await;
+    assert_eq!(cached(&state).await.as_deref(), Some("new"));
+
+    state.invalidate(&pool, Some("new")).await;
+    assert_eq!(cached(&state).await, None);
+
+    // 密钥变更：无条件丢弃
+    let state = BaiduTokenState::with_token("ak", "sk", "tok");
+    state.invalidate(&pool, None).await;
+    assert_eq!(cached(&state).await, None);
+  }
+
+  /// mock OAuth 只应被调用 expected_calls 次，每次都返回 fresh
+  async fn oauth_server(expected_calls: u64) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .and(path("/oauth/2.0/token"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "access_token": "fresh", "expires_in": 2592000 })))
+      .expect(expected_calls)
+      .mount(&server)
+      .await;
+    server
+  }
+
+  #[tokio::test]
+  async fn persisted_token_is_restored_on_load() {
+    let pool = pool_with_baidu_keys();
+    let token = CachedToken {
+      key_hash: credentials_hash("ak", "sk"),
+      access_token: "tok".to_string(),
+      expires_at_unix: unix_now() + 2592000,
+    };
+    persist_token(&pool, &token).unwrap();
+
+    // 库里存的是密文
+    let stored: String = pool
+      .get()
+      .unwrap()
+      .query_row("SELECT access_token FROM baidu_access_token", [], |r| r.get(0))
+      .unwrap();
+    assert_ne!(stored, "tok");
+
+    let loaded = load_persisted_token(&pool).unwrap().unwrap();
+    assert_eq!(
+      (loaded.key_hash.as_str(), loaded.access_token.as_str(), loaded.expires_at_unix),
+      (token.key_hash.as_str(), "tok", token.expires_at_unix)
+    );
+
+    // 重启后直接用恢复的 token，不再调 OAuth
+    let server = oauth_server(0).await;
+    let state = BaiduTokenState::load(&pool);
+    let oauth_url = format!("{}/oauth/2.0/token", server.uri());
+    let got = fetch_access_token(&pool, &Client::new(), &state, &oauth_url).await.unwrap();
+    assert_eq!(got, "tok");
+  }
+
+  #[tokio::test]
+  async fn persisted_token_is_ignored_when_unusable() {
+    let cases = [
+      // 属于另一组密钥
+      (credentials_hash("other", "sk"), unix_now() + 2592000),
+      // 离过期不到 TOKEN_REFRESH_SAFETY_WINDOW
+      (credentials_hash("ak", "sk"), unix_now() + 60 * 60),
+    ];
+
+    for (key_hash, expires_at_unix) in cases {
+      let pool = pool_with_baidu_keys();
+      let token = CachedToken {
+        key_hash,
+        access_token: "stale".to_string(),
+        expires_at_unix,
+      };
+      persist_token(&pool, &token).unwrap();
+      assert!(!token.is_usable(&credentials_hash("ak", "sk")));
+
+      let server = oauth_server(1).await;
+      let state = BaiduTokenState::load(&pool);
+      let oauth_url = format!("{}/oauth/2.0/token", server.uri());
+      let got = fetch_access_token(&pool, &Client::new(), &state, &oauth_url).await.unwrap();
+      assert_eq!(got, "fresh");
+
+      // 新 token 覆盖掉库里的旧 token
+      let persisted = load_persisted_token(&pool).unwrap().unwrap();
+      assert_eq!(persisted.access_token, "fresh");
+      assert_eq!(persisted.key_hash, credentials_hash("ak", "sk"));
+    }
+  }
+
+  #[tokio::test]
+  async fn text_translate_uses_cache_unless_no_cache() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .and(path("/texttrans"))
+      .and(query_param("access_token", "tok"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+        "result": { "from": "en", "to": "zh", "trans_result": [{ "src": "hello", "dst": "你好" }] }
+      })))
+      .expect(2)
+      .mount(&server)
+      .await;
+
+    let pool = pool_with_baidu_keys();
+    let client = Client::new();
+    let state = BaiduTokenState::with_token("ak", "sk", "tok");
+    let endpoint = format!("{}/texttrans", server.uri());
+    let mut payload = TextTranslatePayload {
+      q: "hello".to_string(),
+      from: "en".to_string(),
+      to: "zh".to_string(),
+      term_ids: None,
+      no_cache: None,
+      glossary_id: None,
+    };
+
+    // 第一次请求接口并写缓存，第二次直接命中缓存
+    for _ in 0..2 {
+      let result = text_translate_at(&pool, &client, &state, &endpoint, &payload).await.unwrap();
+      assert_eq!(result.dst, "你好");
+    }
+    assert_eq!(cache::stats(&pool).unwrap().hits, 1);
+
+    // no_cache 跳过缓存再请求一次（mock 一共只应收到 2 次请求）
+    payload.no_cache = Some(true);
+    let result = text_translate_at(&pool, &client, &state, &endpoint, &payload).await.unwrap();
+    assert_eq!(result.dst, "你好");
+    assert_eq!(cache::stats(&pool).unwrap().hits, 1);
+  }
+
+  #[test]
+  fn baidu_error_codes_map_to_kinds() {
+    let cases: &[(serde_json::Value, Option<&str>)] = &[
+      (json!({ "result": {} }), None),
+      (json!({ "error_code": 0 }), None),
+      (json!({ "error_code": "0" }), None),
+      (json!({ "error_code": "" }), None),
+      (json!({ "error_code": 110, "error_msg": "Access token invalid" }), Some("token_invalid")),
+      (json!({ "error_code": "111" }), Some("token_invalid")),
+      (json!({ "error_code": 18 }), Some("rate_limited")),
+      (json!({ "error_code": "54003" }), Some("rate_limited")),
+      (json!({ "error_code": 54005 }), Some("rate_limited")),
+      (json!({ "error_code": 4 }), Some("quota_exhausted")),
+      (json!({ "error_code": 17 }), Some("quota_exhausted")),
+      (json!({ "error_code": 19 }), Some("quota_exhausted")),
+      (json!({ "error_code": "54004" }), Some("quota_exhausted")),
+      (json!({ "error_code": 58001 }), Some("unsupported_language")),
+      (json!({ "error_code": 58002 }), Some("service_disabled")),
+      (json!({ "error_code": 31005, "error_msg": "boom" }), Some("provider")),
+    ];
+
+    for (resp, kind) in cases {
+      let got = check_baidu_error(resp).err().map(|e| {
+        let payload = serde_json::to_value(&e).unwrap();
+        payload["kind"].as_str().unwrap().to_string()
+      });
+      assert_eq!(got.as_deref(), *kind, "{resp}");
+    }
+  }
+
+  #[test]
+  fn baidu_error_keeps_code_and_message() {
+    match baidu_error(BaiduErrorCode::Num(31005), "boom".to_string()) {
+      AppError::Provider { code, msg } => assert_eq!((code.as_str(), msg.as_str()), ("31005", "boom")),
+      other => panic!("{other:?}"),
     }
+    let e = baidu_error(BaiduErrorCode::Str("58002".to_string()), "service closed".to_string());
+    assert_eq!(e.to_string(), "服务未开通或已关闭：service closed (58002)");
+  }
+
+  #[test]
+  fn paste_mode_from_int() {
+    let parse = |v: serde_json::Value| serde_json::from_value::<PasteMode>(v);
+    assert_eq!(parse(json!(0)).unwrap(), PasteMode::None);
+    assert_eq!(parse(json!(1)).unwrap(), PasteMode::Full);
+    assert_eq!(parse(json!(2)).unwrap(), PasteMode::Block);
+    assert!(parse(json!(3)).is_err());
+    assert!(parse(json!("block")).is_err());
+  }
+
+  #[test]
+  fn parse_rect_cases() {
+    let rect = parse_rect("79 23 111 73");
+    assert_eq!((rect.left, rect.top, rect.width, rect.height), (79, 23, 111, 73));
+
+    let rect = parse_rect("  1   2 3\t4 ");
+    assert_eq!((rect.left, rect.top, rect.width, rect.height), (1, 2, 3, 4));
+
+    for bad in ["", "1 2 3", "1 2 3 4 5", "a b c d"] {
+      let rect = parse_rect(bad);
+      assert_eq!((rect.left, rect.top, rect.width, rect.height), (0, 0, 0, 0), "{bad:?}");
+    }
+  }
+
+  fn pic_response() -> serde_json::Value {
+    json!({
+      "error_code": "0",
+      "data": {
+        "from": "en",
+        "to": "zh",
+        "sumSrc": "Hello World",
+        "sumDst": "你好世界",
+        "pasteImg": "FULL",
+        "content": [
+          {
+            "src": "Hello",
+            "dst": "你好",
+            "rect": "10 20 30 40",
+            "lineCount": 1,
+            "pasteImg": "BLOCK0",
+            "points": [{ "x": 10, "y": 20 }, { "x": 40, "y": 20 }, { "x": 40, "y": 60 }, { "x": 10, "y": 60 }]
+          },
+          { "src": "World", "dst": "世界", "rect": "bad", "pasteImg": "BLOCK1" }
+        ]
+      }
+    })
+  }
+
+  #[test]
+  fn pic_response_maps_sections() {
+    let (result, paste_images) = parse_pic_response(pic_response(), PasteMode::None).unwrap();
+    assert!(paste_images.is_empty());
+    assert_eq!((result.from.as_str(), result.to.as_str()), ("en", "zh"));
+    assert_eq!((result.src.as_str(), result.dst.as_str()), ("Hello World", "你好世界"));
+
+    assert_eq!(result.sections.len(), 2);
+    let first = &result.sections[0];
+    assert_eq!((first.src.as_str(), first.dst.as_str(), first.line_count), ("Hello", "你好", 1));
+    assert_eq!((first.rect.left, first.rect.top, first.rect.width, first.rect.height), (10, 20, 30, 40));
+    assert_eq!(first.points.len(), 4);
+    let second = &result.sections[1];
+    assert_eq!((second.line_count, second.rect.width, second.points.len()), (0, 0, 0));
+  }
+
+  #[test]
+  fn pic_response_picks_paste_images_by_mode() {
+    let (_, full) = parse_pic_response(pic_response(), PasteMode::Full).unwrap();
+    assert_eq!(full, vec![(None, "FULL".to_string())]);
+
+    let (_, block) = parse_pic_response(pic_response(), PasteMode::Block).unwrap();
+    assert_eq!(
+      block,
+      vec![(Some(0), "BLOCK0".to_string()), (Some(1), "BLOCK1".to_string())]
+    );
+  }
+
+  #[test]
+  fn pic_response_strips_paste_img_from_raw() {
+    let (result, _) = parse_pic_response(pic_response(), PasteMode::Block).unwrap();
+    let raw = result.raw.to_string();
+    assert!(!raw.contains("pasteImg"), "{raw}");
+    assert_eq!(result.raw["data"]["content"][0]["src"], "Hello");
+  }
+
+  #[test]
+  fn pic_response_without_data_is_error() {
+    assert!(parse_pic_response(json!({ "error_code": "0" }), PasteMode::None).is_err());
   }
 }
@@ -0,0 +1,18 @@
+use tauri::State;
+
+use crate::{
+  cache::{CachePurgePayload, CacheStats},
+  db::DbPool,
+  error::AppError,
+};
+
+#[tauri::command]
+pub fn translate_cache_stats(pool: State<DbPool>) -> Result<CacheStats, AppError> {
+  crate::cache::stats(&pool)
+}
+
+/// 返回删除的条目数
+#[tauri::command]
+pub fn translate_cache_purge(pool: State<DbPool>, payload: CachePurgePayload) -> Result<usize, AppError> {
+  crate::cache::purge(&pool, &payload)
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use super::baidu_translate::{self, BaiduTokenState, PasteMode, PicTranslateResult};
+use crate::{
+  db::DbPool, error::AppError, http::HttpClientState, settings::ProxyService, translate::upload,
+};
+
+// ======= 前端调用参数 / 返回 =======
+
+#[derive(Debug, Deserialize)]
+pub struct ClipboardPicPayload {
+  pub from: String,
+  pub to: String,
+  #[serde(default)]
+  pub paste: PasteMode,
+  /// 为 true 时把整图贴合后的图片写回剪贴板（会强制使用 paste=full）
+  pub write_back: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClipboardPicResult {
+  #[serde(flatten)]
+  pub result: PicTranslateResult,
+  /// 是否已把贴合后的图片写回剪贴板
+  pub written_back: bool,
+}
+
+// ======= commands =======
+
+/// 翻译剪贴板里的图片（截图）：转成 PNG 后走图片翻译，可选把贴合结果写回剪贴板
+#[tauri::command]
+pub async fn baidu_pic_translate_clipboard(
+  app: AppHandle,
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: ClipboardPicPayload,
+) -> Result<ClipboardPicResult, AppError> {
+  let write_back = payload.write_back.unwrap_or(false);
+  let paste = if write_back { PasteMode::Full } else { payload.paste };
+
+  let image = {
+    let clip = app
+      .clipboard()
+      .read_image()
+      .map_err(|e| AppError::msg(format!("剪贴板里没有图片：{e}")))?;
+    upload::encode_png(clip.rgba().to_vec(), clip.width(), clip.height())?
+  };
+
+  let client = http.client(&pool, ProxyService::Baidu).await?;
+  let result =
+    baidu_translate::pic_translate(&pool, &client, &token_state, &image, &payload.from, &payload.to, paste).await?;
+
+  let written_back = match (&result.paste_image, write_back) {
+    (Some(path), true) => {
+      write_image(&app, path).await?;
+      true
+    }
+    _ => false,
+  };
+
+  Ok(ClipboardPicResult { result, written_back })
+}
+
+// ======= helpers =======
+
+/// 贴合后的图片（通常是 JPEG）解码成 RGBA 写回剪贴板
+async fn write_image(app: &AppHandle, path: &str) -> Result<(), AppError> {
+  let bytes = tokio::fs::read(path)
+    .await
+    .map_err(|e| AppError::Io(format!("read {path} failed: {e}")))?;
+
+  let rgba = image::load_from_memory(&bytes)
+    .map_err(|e| AppError::Serde(format!("decode image failed: {e}")))?
+    .to_rgba8();
+  let (width, height) = rgba.dimensions();
+
+  app
+    .clipboard()
+    .write_image(&tauri::image::Image::new_owned(rgba.into_raw(), width, height))
+    .map_err(|e| AppError::msg(format!("写入剪贴板失败：{e}")))
+}
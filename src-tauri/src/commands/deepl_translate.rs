@@ -0,0 +1,345 @@
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::State;
+
+use super::baidu_translate::{TextTranslateResult, TranslationSegment};
+use crate::{
+  db::DbPool,
+  error::AppError,
+  http::HttpClientState,
+  settings::{DeeplKey, ProxyService},
+  translate::ProviderKind,
+};
+
+// ======= DeepL API =======
+// 文本翻译：POST {base}/v2/translate
+// 用量查询：GET  {base}/v2/usage
+// 认证头：Authorization: DeepL-Auth-Key <key>
+// Free 版 key 以 ":fx" 结尾，只能访问 api-free.deepl.com
+
+const DEEPL_FREE_BASE_URL: &str = "https://api-free.deepl.com";
+const DEEPL_PRO_BASE_URL: &str = "https://api.deepl.com";
+
+// ======= 前端调用参数 / 返回 =======
+
+#[derive(Debug, Deserialize)]
+pub struct DeeplTranslatePayload {
+  pub q: String,
+  /// 源语言，不传或传 "auto" 表示自动检测（使用术语表时必填）
+  pub from: Option<String>,
+  /// 目标语言，例如 "ZH" / "EN-US"
+  pub to: String,
+  pub formality: Option<DeeplFormality>,
+  pub glossary_id: Option<String>,
+}
+
+/// 正式程度：只有部分目标语言支持，prefer_* 在不支持时会自动降级
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeeplFormality {
+  Default,
+  More,
+  Less,
+  PreferMore,
+  PreferLess,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeeplUsage {
+  pub character_count: u64,
+  pub character_limit: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeeplTranslateResponse {
+  translations: Vec<DeeplTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeeplTranslation {
+  detected_source_language: Option<String>,
+  text: String,
+}
+
+// ======= commands =======
+
+#[tauri::command]
+pub async fn deepl_translate(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  payload: DeeplTranslatePayload,
+) -> Result<TextTranslateResult, AppError> {
+  let client = http.client(&pool, ProxyService::Deepl).await?;
+  let key = get_deepl_key(&pool)?;
+
+  let result = text_translate(&client, &key, &payload).await?;
+
+  crate::history::record_quietly(&pool, ProviderKind::Deepl.as_str(), &payload.q, &result);
+  Ok(result)
+}
+
+#[tauri::command]
+pub async fn deepl_usage(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+) -> Result<DeeplUsage, AppError> {
+  let client = http.client(&pool, ProxyService::Deepl).await?;
+  let key = get_deepl_key(&pool)?;
+
+  usage(&client, &key).await
+}
+
+// ======= helpers =======
+
+pub(crate) async fn text_translate(
+  client: &Client,
+  key: &DeeplKey,
+  payload: &DeeplTranslatePayload,
+) -> Result<TextTranslateResult, AppError> {
+  let url = format!("{}/v2/translate", base_url(key));
+
+  let mut body = json!({
+    "text": [payload.q],
+    "target_lang": payload.to,
+  });
+  if let Some(from) = payload.from.as_deref().filter(|f| !f.is_empty() && *f != "auto") {
+    body["source_lang"] = json!(from);
+  }
+  if let Some(formality) = payload.formality {
+    body["formality"] = json!(formality);
+  }
+  if let Some(glossary_id) = payload.glossary_id.as_deref().filter(|g| !g.is_empty()) {
+    if body.get("source_lang").is_none() {
+      return Err(AppError::msg("使用 DeepL 术语表时必须指定源语言"));
+    }
+    body["glossary_id"] = json!(glossary_id);
+  }
+
+  let resp = client
+    .post(url)
+    .header("Authorization", format!("DeepL-Auth-Key {}", key.api_key))
+    .json(&body)
+    .send()
+    .await
+    .map_err(AppError::from)?;
+
+  let resp_json = read_json(resp).await?;
+  let parsed: DeeplTranslateResponse = serde_json::from_value(resp_json.clone())?;
+
+  let from = parsed
+    .translations
+    .first()
+    .and_then(|t| t.detected_source_language.clone())
+    .or_else(|| payload.from.clone())
+    .unwrap_or_default();
+
+  let dst = parsed
+    .translations
+    .into_iter()
+    .map(|t| t.text)
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  Ok(TextTranslateResult {
+    from,
+    to: payload.to.clone(),
+    segments: vec![TranslationSegment {
+      src: payload.q.clone(),
+      dst: dst.clone(),
+    }],
+    dst,
+    raw: resp_json,
+  })
+}
+
+pub(crate) async fn usage(client: &Client, key: &DeeplKey) -> Result<DeeplUsage, AppError> {
+  let url = format!("{}/v2/usage", base_url(key));
+
+  let resp = client
+    .get(url)
+    .header("Authorization", format!("DeepL-Auth-Key {}", key.api_key))
+    .send()
+    .await
+    .map_err(AppError::from)?;
+
+  Ok(serde_json::from_value(read_json(resp).await?)?)
+}
+
+/// 设置里填了 endpoint 就用它（兼容填成完整的 /v2/translate 地址），否则按 key 类型选择 free / pro
+fn base_url(key: &DeeplKey) -> String {
+  let endpoint = key.endpoint.trim().trim_end_matches('/');
+  if !endpoint.is_empty() {
+    let base = endpoint.find("/v2").map(|i| &endpoint[..i]).unwrap_or(endpoint);
+    return base.to_string();
+  }
+
+  if key.api_key.trim().ends_with(":fx") {
+    DEEPL_FREE_BASE_URL.to_string()
+  } else {
+    DEEPL_PRO_BASE_URL.to_string()
+  }
+}
+
+/// DeepL 用 HTTP 状态码表达错误，错误体为 {"message": "..."}
+async fn read_json(resp: reqwest::Response) -> Result<serde_json::Value, AppError> {
+  let status = resp.status();
+  if status.is_success() {
+    return resp.json().await.map_err(AppError::from);
+  }
+
+  let text = resp.text().await.unwrap_or_default();
+  let detail = serde_json::from_str::<serde_json::Value>(&text)
+    .ok()
+    .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(|s| s.to_string()))
+    .unwrap_or(text);
+
+  let hint = match status {
+    StatusCode::FORBIDDEN => "DeepL API Key 无效，或 Free/Pro 与 endpoint 不匹配",
+    StatusCode::TOO_MANY_REQUESTS => "请求过于频繁，请稍后再试",
+    // 456 Quota Exceeded
+    s if s.as_u16() == 456 => "本月字符额度已用完",
+    _ => "DeepL 接口返回错误",
+  };
+
+  Err(AppError::msg(format!("{hint} ({status}): {detail}")))
+}
+
+pub(crate) fn get_deepl_key(pool: &DbPool) -> Result<DeeplKey, AppError> {
+  let key = crate::settings::get_api_keys(pool)?
+    .ok_or_else(|| AppError::msg("未配置 API Keys，请先在设置中保存"))?
+    .translation
+    .deepl;
+
+  if key.api_key.trim().is_empty() {
+    return Err(AppError::msg("DeepL API Key 为空，请先在设置里填写"));
+  }
+
+  Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use wiremock::{
+    matchers::{body_partial_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+  };
+
+  fn key(api_key: &str, endpoint: &str) -> DeeplKey {
+    DeeplKey {
+      api_key: api_key.to_string(),
+      endpoint: endpoint.to_string(),
+    }
+  }
+
+  fn payload(q: &str) -> DeeplTranslatePayload {
+    DeeplTranslatePayload {
+      q: q.to_string(),
+      from: Some("auto".to_string()),
+      to: "ZH".to_string(),
+      formality: None,
+      glossary_id: None,
+    }
+  }
+
+  #[test]
+  fn base_url_by_key_type() {
+    assert_eq!(base_url(&key("abc:fx", "")), DEEPL_FREE_BASE_URL);
+    assert_eq!(base_url(&key(" abc:fx \n", "")), DEEPL_FREE_BASE_URL);
+    assert_eq!(base_url(&key("abc", "")), DEEPL_PRO_BASE_URL);
+    assert_eq!(base_url(&key("abc:fx-not", "  ")), DEEPL_PRO_BASE_URL);
+  }
+
+  #[test]
+  fn base_url_prefers_endpoint() {
+    assert_eq!(base_url(&key("abc:fx", "https://proxy.example.com/")), "https://proxy.example.com");
+    assert_eq!(
+      base_url(&key("abc", "https://api-free.deepl.com/v2/translate")),
+      "https://api-free.deepl.com"
+    );
+  }
+
+  #[tokio::test]
+  async fn maps_successful_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .and(path("/v2/translate"))
+      .and(header("Authorization", "DeepL-Auth-Key abc:fx"))
+      .and(body_partial_json(serde_json::json!({
+        "text": ["hello"],
+        "target_lang": "ZH",
+        "formality": "prefer_more"
+      })))
+      .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "translations": [{ "detected_source_language": "EN", "text": "你好" }]
+      })))
+      .expect(1)
+      .mount(&server)
+      .await;
+
+    let mut p = payload("hello");
+    p.formality = Some(DeeplFormality::PreferMore);
+    let result = text_translate(&Client::new(), &key("abc:fx", &server.uri()), &p)
+      .await
+      .unwrap();
+
+    assert_eq!(result.from, "EN");
+    assert_eq!(result.to, "ZH");
+    assert_eq!(result.dst, "你好");
+    assert_eq!(result.segments.len(), 1);
+    assert_eq!(result.segments[0].src, "hello");
+  }
+
+  #[tokio::test]
+  async fn glossary_requires_source_lang() {
+    let mut p = payload("hello");
+    p.glossary_id = Some("g1".to_string());
+
+    // 在发请求之前就报错，不需要 mock
+    let err = text_translate(&Client::new(), &key("abc", "http://127.0.0.1:9"), &p)
+      .await
+      .unwrap_err();
+    assert!(err.to_string().contains("源语言"));
+  }
+
+  #[tokio::test]
+  async fn surfaces_http_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .respond_with(ResponseTemplate::new(456).set_body_json(serde_json::json!({
+        "message": "Quota exceeded"
+      })))
+      .mount(&server)
+      .await;
+
+    let err = text_translate(&Client::new(), &key("abc", &server.uri()), &payload("hello"))
+      .await
+      .unwrap_err()
+      .to_string();
+
+    assert!(err.contains("额度"), "{err}");
+    assert!(err.contains("Quota exceeded"), "{err}");
+  }
+
+  #[tokio::test]
+  async fn reads_usage() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .and(path("/v2/usage"))
+      .and(header("Authorization", "DeepL-Auth-Key abc"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "character_count": 1200,
+        "character_limit": 500000
+      })))
+      .expect(1)
+      .mount(&server)
+      .await;
+
+    let endpoint = format!("{}/v2/translate", server.uri());
+    let usage = usage(&Client::new(), &key("abc", &endpoint)).await.unwrap();
+
+    assert_eq!(usage.character_count, 1200);
+    assert_eq!(usage.character_limit, 500000);
+  }
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+use tauri::{AppHandle, State};
+
+use crate::{db::DbPool, doc_job::DocJob, error::AppError};
+
+#[derive(Debug, Deserialize)]
+pub struct DocJobIdPayload {
+  pub id: String,
+}
+
+/// 文档翻译任务列表（按创建时间倒序）；进度变化另见 DOC_PROGRESS_EVENT
+#[tauri::command]
+pub fn doc_translate_job_list(pool: State<DbPool>) -> Result<Vec<DocJob>, AppError> {
+  crate::doc_job::list(&pool)
+}
+
+/// 删除任务记录（已下载的译文文件不删）
+#[tauri::command]
+pub fn doc_translate_job_delete(pool: State<DbPool>, payload: DocJobIdPayload) -> Result<(), AppError> {
+  crate::doc_job::delete(&pool, &payload.id)
+}
+
+/// 失败的任务重新轮询 / 下载
+#[tauri::command]
+pub fn doc_translate_job_retry(app: AppHandle, payload: DocJobIdPayload) -> Result<DocJob, AppError> {
+  crate::doc_job::retry(&app, &payload.id)
+}
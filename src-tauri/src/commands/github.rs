@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::{db::DbPool, http::HttpClientState, settings::ProxyService};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct RepoCommitHeatmap {
@@ -19,13 +22,18 @@ pub struct Week {
 }
 
 #[tauri::command]
-pub async fn github_repo_commit_activity(owner: String, repo: String) -> Result<RepoCommitHeatmap, String> {
+pub async fn github_repo_commit_activity(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  owner: String,
+  repo: String,
+) -> Result<RepoCommitHeatmap, String> {
   let url = format!(
     "https://api.github.com/repos/{}/{}/stats/commit_activity",
     owner, repo
   );
 
-  let client = reqwest::Client::new();
+  let client = http.client(&pool, ProxyService::Github).await.map_err(|e| e.to_string())?;
   let resp = client
     .get(url)
     .header(reqwest::header::USER_AGENT, "ADuiTools")
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use tauri::State;
+
+use crate::{
+  db::DbPool,
+  error::AppError,
+  glossary::{Glossary, GlossaryImportResult, GlossaryPayload, GlossaryTerm},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct GlossaryUpdatePayload {
+  pub id: i64,
+  #[serde(flatten)]
+  pub glossary: GlossaryPayload,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GlossaryImportPayload {
+  pub glossary_id: i64,
+  /// .csv / .tsv / .txt 文件路径
+  pub path: String,
+  /// 为 true 时先清空术语表里原有的术语
+  pub replace: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GlossaryTermPayload {
+  pub glossary_id: i64,
+  #[serde(flatten)]
+  pub term: GlossaryTerm,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GlossaryTermDeletePayload {
+  pub glossary_id: i64,
+  pub source: String,
+}
+
+#[tauri::command]
+pub fn glossary_list(pool: State<DbPool>) -> Result<Vec<Glossary>, AppError> {
+  crate::glossary::list(&pool)
+}
+
+#[tauri::command]
+pub fn glossary_create(pool: State<DbPool>, payload: GlossaryPayload) -> Result<Glossary, AppError> {
+  crate::glossary::create(&pool, &payload)
+}
+
+#[tauri::command]
+pub fn glossary_update(pool: State<DbPool>, payload: GlossaryUpdatePayload) -> Result<Glossary, AppError> {
+  crate::glossary::update(&pool, payload.id, &payload.glossary)
+}
+
+#[tauri::command]
+pub fn glossary_delete(pool: State<DbPool>, id: i64) -> Result<(), AppError> {
+  crate::glossary::delete(&pool, id)
+}
+
+#[tauri::command]
+pub fn glossary_terms(pool: State<DbPool>, glossary_id: i64) -> Result<Vec<GlossaryTerm>, AppError> {
+  crate::glossary::terms(&pool, glossary_id)
+}
+
+/// 从 CSV / TSV 文件导入术语：.tsv 按 tab 分隔，.csv 按逗号分隔，其他扩展名按内容判断
+#[tauri::command]
+pub async fn glossary_import(
+  pool: State<'_, DbPool>,
+  payload: GlossaryImportPayload,
+) -> Result<GlossaryImportResult, AppError> {
+  let path = Path::new(&payload.path);
+  let content = tokio::fs::read_to_string(path)
+    .await
+    .map_err(|e| AppError::Io(format!("read {} failed: {e}", path.display())))?;
+
+  let delimiter = match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+    Some("tsv") => Some('\t'),
+    Some("csv") => Some(','),
+    _ => None,
+  };
+
+  crate::glossary::import(
+    &pool,
+    payload.glossary_id,
+    &content,
+    delimiter,
+    payload.replace.unwrap_or(false),
+  )
+}
+
+#[tauri::command]
+pub fn glossary_upsert_term(pool: State<DbPool>, payload: GlossaryTermPayload) -> Result<(), AppError> {
+  crate::glossary::upsert_term(&pool, payload.glossary_id, &payload.term)
+}
+
+#[tauri::command]
+pub fn glossary_delete_term(pool: State<DbPool>, payload: GlossaryTermDeletePayload) -> Result<(), AppError> {
+  crate::glossary::delete_term(&pool, payload.glossary_id, &payload.source)
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+use tauri::State;
+
+use crate::{
+  db::DbPool,
+  error::AppError,
+  history::{HistoryPage, HistoryQuery},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryFavoritePayload {
+  pub id: i64,
+  pub favorite: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryDeletePayload {
+  pub ids: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryClearPayload {
+  /// 为 true 时保留已收藏的记录
+  pub keep_favorites: Option<bool>,
+}
+
+#[tauri::command]
+pub fn history_list(pool: State<DbPool>, payload: HistoryQuery) -> Result<HistoryPage, AppError> {
+  crate::history::list(&pool, &payload)
+}
+
+/// 全文搜索：原文和译文都参与匹配（FTS5 trigram，关键词少于 3 个字时退化为 LIKE）
+#[tauri::command]
+pub fn history_search(pool: State<DbPool>, payload: HistoryQuery) -> Result<HistoryPage, AppError> {
+  if payload.keyword.as_deref().map(str::trim).unwrap_or("").is_empty() {
+    return Err(AppError::msg("搜索关键词不能为空"));
+  }
+  crate::history::list(&pool, &payload)
+}
+
+#[tauri::command]
+pub fn history_set_favorite(pool: State<DbPool>, payload: HistoryFavoritePayload) -> Result<(), AppError> {
+  crate::history::set_favorite(&pool, payload.id, payload.favorite)
+}
+
+#[tauri::command]
+pub fn history_delete(pool: State<DbPool>, payload: HistoryDeletePayload) -> Result<(), AppError> {
+  crate::history::delete(&pool, &payload.ids)
+}
+
+#[tauri::command]
+pub fn history_clear(pool: State<DbPool>, payload: HistoryClearPayload) -> Result<(), AppError> {
+  crate::history::clear(&pool, payload.keep_favorites.unwrap_or(false))
+}
@@ -0,0 +1,149 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use super::baidu_translate::{self, BaiduTokenState, BatchTranslatePayload};
+use crate::{
+  db::DbPool,
+  error::AppError,
+  http::HttpClientState,
+  i18n::{placeholder, I18nFormat},
+  settings::ProxyService,
+};
+
+// ======= 前端调用参数 / 返回 =======
+
+#[derive(Debug, Deserialize)]
+pub struct I18nTranslatePayload {
+  /// 源语言资源文件（例如 locales/zh.json）
+  pub source_path: String,
+  /// 输出文件；已存在时会被覆盖
+  pub target_path: String,
+  pub from: String,
+  pub to: String,
+  /// 不传时按 source_path 扩展名识别
+  pub format: Option<I18nFormat>,
+  /// 缺失键模式：目标文件里已有（非空）译文的键原样保留，只翻译缺失的键
+  pub missing_only: Option<bool>,
+  pub term_ids: Option<String>,
+  pub qps: Option<u32>,
+  pub no_cache: Option<bool>,
+  /// 透传给批量翻译的进度事件
+  pub batch_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct I18nTranslateResult {
+  pub target_path: String,
+  pub format: I18nFormat,
+  /// 源文件中的字符串条目数
+  pub total: usize,
+  /// 本次调用接口翻译的条目数
+  pub translated: usize,
+  /// 沿用目标文件已有译文的条目数
+  pub reused: usize,
+  /// 占位符在译文中丢失或重复的键：这些键保留原文，需要人工处理
+  pub placeholder_issues: Vec<String>,
+}
+
+// ======= commands =======
+
+/// 翻译 i18n 资源文件（JSON / YAML / .properties / .po），按源文件的结构和键顺序输出
+#[tauri::command]
+pub async fn i18n_translate_file(
+  app: AppHandle,
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: I18nTranslatePayload,
+) -> Result<I18nTranslateResult, AppError> {
+  let source_path = Path::new(&payload.source_path);
+  let target_path = Path::new(&payload.target_path);
+
+  let format = payload
+    .format
+    .or_else(|| I18nFormat::from_path(source_path))
+    .ok_or_else(|| AppError::msg("无法识别资源文件格式，请指定 format（json / yaml / properties / po）"))?;
+
+  let mut file = format.parse(&read_file(source_path).await?)?;
+
+  let existing = if payload.missing_only.unwrap_or(false) && target_path.exists() {
+    format.parse(&read_file(target_path).await?)?.translated_entries()
+  } else {
+    HashMap::new()
+  };
+
+  let entries = file.source_entries();
+  let mut translations = HashMap::new();
+  let mut pending = Vec::new();
+  let mut reused = 0;
+
+  for (key, text) in &entries {
+    if let Some(done) = existing.get(key) {
+      translations.insert(key.clone(), done.clone());
+      reused += 1;
+      continue;
+    }
+
+    let protected = placeholder::protect(text);
+    if protected.is_translatable() {
+      pending.push((key, text, protected));
+    } else {
+      // 空串 / 纯占位符：原样输出
+      translations.insert(key.clone(), text.clone());
+    }
+  }
+
+  let mut placeholder_issues = Vec::new();
+  if !pending.is_empty() {
+    let client = http.client(&pool, ProxyService::Baidu).await?;
+    let batch = BatchTranslatePayload {
+      items: pending.iter().map(|(_, _, p)| p.text.clone()).collect(),
+      from: payload.from.clone(),
+      to: payload.to.clone(),
+      term_ids: payload.term_ids.clone(),
+      qps: payload.qps,
+      no_cache: payload.no_cache,
+      batch_id: payload.batch_id.clone(),
+    };
+    let result = baidu_translate::translate_batch(&app, &pool, &client, &token_state, &batch).await?;
+
+    for ((key, text, protected), dst) in pending.iter().zip(result.items) {
+      let value = protected.restore(&dst).unwrap_or_else(|| {
+        placeholder_issues.push((*key).clone());
+        (*text).clone()
+      });
+      translations.insert((*key).clone(), value);
+    }
+  }
+
+  file.apply(&translations);
+  let rendered = file.render()?;
+
+  if let Some(dir) = target_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+    tokio::fs::create_dir_all(dir)
+      .await
+      .map_err(|e| AppError::Io(format!("create dir failed: {e}")))?;
+  }
+  tokio::fs::write(target_path, rendered)
+    .await
+    .map_err(|e| AppError::Io(format!("write {} failed: {e}", target_path.display())))?;
+
+  Ok(I18nTranslateResult {
+    target_path: payload.target_path.clone(),
+    format,
+    total: entries.len(),
+    translated: pending.len() - placeholder_issues.len(),
+    reused,
+    placeholder_issues,
+  })
+}
+
+// ======= helpers =======
+
+async fn read_file(path: &Path) -> Result<String, AppError> {
+  tokio::fs::read_to_string(path)
+    .await
+    .map_err(|e| AppError::Io(format!("read {} failed: {e}", path.display())))
+}
@@ -1,3 +1,12 @@
 pub mod settings;
 pub mod baidu_translate;
 pub mod github;
+pub mod youdao_translate;
+pub mod deepl_translate;
+pub mod translate;
+pub mod history;
+pub mod i18n;
+pub mod cache;
+pub mod glossary;
+pub mod doc_job;
+pub mod clipboard;
@@ -1,6 +1,13 @@
+use serde::Deserialize;
 use tauri::State;
 
-use crate::{db::DbPool, error::AppError, settings::{ApiKeysForm, NetworkProxyForm}};
+use super::baidu_translate::BaiduTokenState;
+use crate::{
+  db::DbPool,
+  error::AppError,
+  http::{HttpClientState, NetworkTestPayload, NetworkTestResult, SystemProxyInfo},
+  settings::{ApiKeysForm, NetworkProxyForm, NetworkProxyOverrides},
+};
 
 #[tauri::command]
 pub fn settings_get_api_keys(pool: State<DbPool>) -> Result<Option<ApiKeysForm>, AppError> {
@@ -8,8 +15,15 @@ pub fn settings_get_api_keys(pool: State<DbPool>) -> Result<Option<ApiKeysForm>,
 }
 
 #[tauri::command]
-pub fn settings_save_api_keys(pool: State<DbPool>, payload: ApiKeysForm) -> Result<(), AppError> {
-  crate::settings::save_api_keys(&pool, &payload)
+pub async fn settings_save_api_keys(
+  pool: State<'_, DbPool>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: ApiKeysForm,
+) -> Result<(), AppError> {
+  crate::settings::save_api_keys(&pool, &payload)?;
+  // 密钥可能变了：旧 token 属于旧的 API Key，下次请求重新获取
+  token_state.invalidate(&pool, None).await;
+  Ok(())
 }
 
 #[tauri::command]
@@ -20,7 +34,60 @@ pub fn settings_get_network(pool: State<DbPool>) -> Result<Option<NetworkProxyFo
 #[tauri::command]
 pub fn settings_save_network(
   pool: State<DbPool>,
+  http: State<HttpClientState>,
   payload: NetworkProxyForm,
 ) -> Result<(), AppError> {
-  crate::settings::save_network(&pool, &payload)
+  crate::settings::save_network(&pool, &payload)?;
+  // 代理变了：丢弃旧客户端，下次请求按新设置重建
+  http.invalidate();
+  Ok(())
+}
+
+/// 用未保存的代理设置探测连通性（延迟、状态码、出口 IP、错误分类）
+#[tauri::command]
+pub async fn settings_test_network(payload: NetworkTestPayload) -> Result<NetworkTestResult, AppError> {
+  Ok(crate::http::test_network(payload).await)
+}
+
+/// 系统代理模式下实际解析到的代理及来源（env / gnome / kde）；每次都重新检测并刷新缓存
+#[tauri::command]
+pub async fn settings_resolve_system_proxy(
+  http: State<'_, HttpClientState>,
+) -> Result<SystemProxyInfo, AppError> {
+  http.refresh_system_proxy().await
+}
+
+#[tauri::command]
+pub fn settings_get_network_overrides(
+  pool: State<DbPool>,
+) -> Result<NetworkProxyOverrides, AppError> {
+  crate::settings::get_network_overrides(&pool)
+}
+
+#[tauri::command]
+pub fn settings_save_network_overrides(
+  pool: State<DbPool>,
+  http: State<HttpClientState>,
+  payload: NetworkProxyOverrides,
+) -> Result<(), AppError> {
+  crate::settings::save_network_overrides(&pool, &payload)?;
+  http.invalidate();
+  Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocOutputDirPayload {
+  /// 不传或传空字符串：使用默认目录
+  pub dir: Option<String>,
+}
+
+/// 文档翻译结果下载目录（None 表示使用默认的系统下载目录）
+#[tauri::command]
+pub fn settings_get_doc_output_dir(pool: State<DbPool>) -> Result<Option<String>, AppError> {
+  crate::settings::get_doc_output_dir(&pool)
+}
+
+#[tauri::command]
+pub fn settings_save_doc_output_dir(pool: State<DbPool>, payload: DocOutputDirPayload) -> Result<(), AppError> {
+  crate::settings::save_doc_output_dir(&pool, payload.dir.as_deref())
 }
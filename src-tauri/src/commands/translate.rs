@@ -0,0 +1,299 @@
+use std::{
+  future::Future,
+  time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::baidu_translate::{BaiduTokenState, TextTranslateResult};
+use crate::{
+  db::DbPool,
+  error::AppError,
+  http::HttpClientState,
+  translate::{self, ProviderKind, TranslateContext, TranslateRequest},
+};
+
+/// 对比模式下单个服务的默认超时
+const DEFAULT_COMPARE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ======= 前端调用参数 / 返回 =======
+
+#[derive(Debug, Deserialize)]
+pub struct TranslateTextPayload {
+  pub provider: ProviderKind,
+  #[serde(flatten)]
+  pub request: TranslateRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetectLanguagePayload {
+  pub provider: ProviderKind,
+  pub q: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TranslateComparePayload {
+  #[serde(flatten)]
+  pub request: TranslateRequest,
+  /// 单个服务的超时（毫秒），默认 DEFAULT_COMPARE_TIMEOUT
+  pub timeout_ms: Option<u64>,
+}
+
+/// 对比模式中某个服务的结果：result / error 二选一
+#[derive(Debug, Serialize)]
+pub struct CompareEntry {
+  pub provider: ProviderKind,
+  pub latency_ms: u64,
+  pub result: Option<TextTranslateResult>,
+  pub error: Option<String>,
+}
+
+// ======= commands =======
+
+/// 统一文本翻译入口：{ provider, q, from, to, glossary_id? }
+#[tauri::command]
+pub async fn translate_text(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: TranslateTextPayload,
+) -> Result<TextTranslateResult, AppError> {
+  let ctx = TranslateContext {
+    pool: &pool,
+    http: &http,
+    baidu_token: &token_state,
+  };
+
+  let result = translate::translate(&ctx, payload.provider, &payload.request).await?;
+
+  crate::history::record_quietly(&pool, payload.provider.as_str(), &payload.request.q, &result);
+  Ok(result)
+}
+
+#[tauri::command]
+pub async fn translate_detect_language(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: DetectLanguagePayload,
+) -> Result<String, AppError> {
+  let ctx = TranslateContext {
+    pool: &pool,
+    http: &http,
+    baidu_token: &token_state,
+  };
+
+  translate::provider(payload.provider)
+    .detect_language(&ctx, &payload.q)
+    .await
+}
+
+#[tauri::command]
+pub fn translate_supported_languages(provider: ProviderKind) -> Vec<&'static str> {
+  translate::provider(provider).supported_languages()
+}
+
+/// 同一段文本并发发给所有已配置密钥的服务，每个服务单独超时，互不阻塞
+#[tauri::command]
+pub async fn translate_compare(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  token_state: State<'_, BaiduTokenState>,
+  payload: TranslateComparePayload,
+) -> Result<Vec<CompareEntry>, AppError> {
+  let keys = crate::settings::get_api_keys(&pool)?
+    .map(|k| k.translation)
+    .unwrap_or_default();
+
+  let providers: Vec<ProviderKind> = ProviderKind::ALL
+    .into_iter()
+    .filter(|p| p.is_configured(&keys))
+    .collect();
+  if providers.is_empty() {
+    return Err(AppError::msg("没有已配置密钥的翻译服务，请先在设置中填写"));
+  }
+
+  let timeout = payload
+    .timeout_ms
+    .map(Duration::from_millis)
+    .unwrap_or(DEFAULT_COMPARE_TIMEOUT);
+  let ctx = TranslateContext {
+    pool: &pool,
+    http: &http,
+    baidu_token: &token_state,
+  };
+  let ctx = &ctx;
+  let request = &payload.request;
+
+  Ok(compare(providers, timeout, |provider| translate::translate(ctx, provider, request)).await)
+}
+
+// ======= helpers =======
+
+/// 并发调用每个服务，各自计时、各自超时；run 负责实际翻译一次
+async fn compare<F, Fut>(providers: Vec<ProviderKind>, timeout: Duration, run: F) -> Vec<CompareEntry>
+where
+  F: Fn(ProviderKind) -> Fut,
+  Fut: Future<Output = Result<TextTranslateResult, AppError>>,
+{
+  let tasks = providers.into_iter().map(|provider| {
+    let run = &run;
+    async move {
+      let started = Instant::now();
+      let outcome = tokio::time::timeout(timeout, run(provider)).await;
+      let latency_ms = started.elapsed().as_millis() as u64;
+
+      let (result, error) = match outcome {
+        Ok(Ok(r)) => (Some(r), None),
+        Ok(Err(e)) => (None, Some(e.to_string())),
+        Err(_) => (None, Some(format!("请求超时（{} ms）", timeout.as_millis()))),
+      };
+
+      CompareEntry {
+        provider,
+        latency_ms,
+        result,
+        error,
+      }
+    }
+  });
+
+  futures::future::join_all(tasks).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    commands::{
+      baidu_translate::{self, TextTranslatePayload},
+      deepl_translate::{self, DeeplTranslatePayload},
+      youdao_translate::{self, YoudaoTextPayload},
+    },
+    settings::{ApiKeysForm, AppPair, DeeplKey},
+  };
+  use r2d2_sqlite::SqliteConnectionManager;
+  use reqwest::Client;
+  use serde_json::json;
+  use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+  };
+
+  /// 百度文本翻译要读密钥、写缓存：内存库里存好 ak/sk
+  fn pool_with_baidu_keys() -> DbPool {
+    crate::crypto::init_for_test();
+
+    let pool = r2d2::Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory())
+      .unwrap();
+    crate::db::migrate::migrate(&mut pool.get().unwrap()).unwrap();
+
+    let mut keys = ApiKeysForm::default();
+    keys.translation.baidu.api_key = "ak".to_string();
+    keys.translation.baidu.app_secret = "sk".to_string();
+    crate::settings::save_api_keys(&pool, &keys).unwrap();
+    pool
+  }
+
+  #[tokio::test]
+  async fn compare_reports_each_provider_separately() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .and(path("/baidu"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+        "result": { "from": "en", "to": "zh", "trans_result": [{ "src": "hello", "dst": "你好" }] }
+      })))
+      .mount(&server)
+      .await;
+    Mock::given(method("POST"))
+      .and(path("/youdao"))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(json!({ "errorCode": "0", "translation": ["你好"], "l": "en2zh-CHS" }))
+          .set_delay(Duration::from_secs(2)),
+      )
+      .mount(&server)
+      .await;
+    Mock::given(method("POST"))
+      .and(path("/v2/translate"))
+      .respond_with(ResponseTemplate::new(456).set_body_json(json!({ "message": "Quota Exceeded" })))
+      .mount(&server)
+      .await;
+
+    let pool = pool_with_baidu_keys();
+    let client = Client::new();
+    let state = BaiduTokenState::with_token("ak", "sk", "tok");
+    let baidu_url = format!("{}/baidu", server.uri());
+    let youdao_url = format!("{}/youdao", server.uri());
+    let youdao_keys = AppPair {
+      app_id: "app".to_string(),
+      app_secret: "secret".to_string(),
+    };
+    let deepl_key = DeeplKey {
+      api_key: "abc".to_string(),
+      endpoint: server.uri(),
+    };
+
+    let run = |provider: ProviderKind| {
+      let (pool, client, state) = (&pool, &client, &state);
+      let (baidu_url, youdao_url, youdao_keys, deepl_key) = (&baidu_url, &youdao_url, &youdao_keys, &deepl_key);
+      async move {
+        match provider {
+          ProviderKind::Baidu => {
+            let payload = TextTranslatePayload {
+              q: "hello".to_string(),
+              from: "en".to_string(),
+              to: "zh".to_string(),
+              term_ids: None,
+              no_cache: None,
+              glossary_id: None,
+            };
+            baidu_translate::text_translate_at(pool, client, state, baidu_url, &payload).await
+          }
+          ProviderKind::Youdao => {
+            let payload = YoudaoTextPayload {
+              q: "hello".to_string(),
+              from: "en".to_string(),
+              to: "zh-CHS".to_string(),
+            };
+            youdao_translate::text_translate(client, youdao_url, youdao_keys, &payload).await
+          }
+          ProviderKind::Deepl => {
+            let payload = DeeplTranslatePayload {
+              q: "hello".to_string(),
+              from: None,
+              to: "ZH".to_string(),
+              formality: None,
+              glossary_id: None,
+            };
+            deepl_translate::text_translate(client, deepl_key, &payload).await
+          }
+        }
+      }
+    };
+
+    let timeout = Duration::from_millis(300);
+    let entries = compare(ProviderKind::ALL.to_vec(), timeout, run).await;
+
+    // 顺序与传入的 providers 一致，result / error 二选一
+    let kinds: Vec<_> = entries.iter().map(|e| e.provider).collect();
+    assert_eq!(kinds, ProviderKind::ALL);
+
+    let baidu = &entries[0];
+    assert_eq!(baidu.result.as_ref().unwrap().dst, "你好");
+    assert!(baidu.error.is_none());
+
+    let youdao = &entries[1];
+    assert!(youdao.result.is_none());
+    assert_eq!(youdao.error.as_deref(), Some("请求超时（300 ms）"));
+    assert!((300..2000).contains(&youdao.latency_ms), "{}", youdao.latency_ms);
+
+    let deepl = &entries[2];
+    assert!(deepl.result.is_none());
+    let error = deepl.error.as_deref().unwrap();
+    assert!(error.contains("本月字符额度已用完") && error.contains("Quota Exceeded"), "{error}");
+  }
+}
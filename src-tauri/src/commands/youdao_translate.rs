@@ -0,0 +1,263 @@
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tauri::State;
+
+use super::baidu_translate::{TextTranslateResult, TranslationSegment};
+use crate::{
+  db::DbPool,
+  error::AppError,
+  http::HttpClientState,
+  settings::{AppPair, ProxyService},
+  translate::ProviderKind,
+};
+
+// ======= 有道智云 API =======
+// 文本翻译（v3 签名）
+// POST https://openapi.youdao.com/api  (application/x-www-form-urlencoded)
+// sign = sha256(appKey + input + salt + curtime + appSecret)
+// input = q 长度 <= 20 ? q : q 前 10 个字符 + q 长度 + q 后 10 个字符
+
+pub(crate) const YOUDAO_TEXT_URL: &str = "https://openapi.youdao.com/api";
+
+// ======= 前端调用参数 =======
+
+#[derive(Debug, Deserialize)]
+pub struct YoudaoTextPayload {
+  pub q: String,
+  pub from: String, // 可传 "auto"
+  pub to: String,   // 中文是 "zh-CHS"
+}
+
+/// 有道文本翻译响应（只取用得到的字段）
+#[derive(Debug, Deserialize)]
+struct YoudaoTextResponse {
+  #[serde(rename = "errorCode")]
+  error_code: String,
+  translation: Option<Vec<String>>,
+  /// 语言方向，例如 "en2zh-CHS"
+  l: Option<String>,
+}
+
+// ======= commands =======
+
+#[tauri::command]
+pub async fn youdao_text_translate(
+  pool: State<'_, DbPool>,
+  http: State<'_, HttpClientState>,
+  payload: YoudaoTextPayload,
+) -> Result<TextTranslateResult, AppError> {
+  let client = http.client(&pool, ProxyService::Youdao).await?;
+  let keys = get_youdao_keys(&pool)?;
+
+  let result = text_translate(&client, YOUDAO_TEXT_URL, &keys, &payload).await?;
+
+  crate::history::record_quietly(&pool, ProviderKind::Youdao.as_str(), &payload.q, &result);
+  Ok(result)
+}
+
+// ======= helpers =======
+
+pub(crate) async fn text_translate(
+  client: &Client,
+  endpoint: &str,
+  keys: &AppPair,
+  payload: &YoudaoTextPayload,
+) -> Result<TextTranslateResult, AppError> {
+  let salt = uuid::Uuid::new_v4().to_string();
+  let curtime = chrono::Utc::now().timestamp().to_string();
+  let sign = sign_v3(&keys.app_id, &keys.app_secret, &payload.q, &salt, &curtime);
+
+  let form = [
+    ("q", payload.q.as_str()),
+    ("from", payload.from.as_str()),
+    ("to", payload.to.as_str()),
+    ("appKey", keys.app_id.as_str()),
+    ("salt", salt.as_str()),
+    ("sign", sign.as_str()),
+    ("signType", "v3"),
+    ("curtime", curtime.as_str()),
+  ];
+
+  let resp_json: serde_json::Value = client
+    .post(endpoint)
+    .form(&form)
+    .send()
+    .await
+    .map_err(AppError::from)?
+    .json()
+    .await
+    .map_err(AppError::from)?;
+
+  let resp: YoudaoTextResponse = serde_json::from_value(resp_json.clone())?;
+
+  if resp.error_code != "0" {
+    return Err(AppError::msg(format!(
+      "Youdao translate failed: {} ({})",
+      error_message(&resp.error_code),
+      resp.error_code
+    )));
+  }
+
+  // l = "en2zh-CHS"：拆出实际的源语言/目标语言（from=auto 时可以拿到检测结果）
+  let (from, to) = resp
+    .l
+    .as_deref()
+    .and_then(|l| l.split_once('2'))
+    .map(|(f, t)| (f.to_string(), t.to_string()))
+    .unwrap_or_else(|| (payload.from.clone(), payload.to.clone()));
+
+  let dst = resp.translation.unwrap_or_default().join("\n");
+
+  Ok(TextTranslateResult {
+    from,
+    to,
+    segments: vec![TranslationSegment {
+      src: payload.q.clone(),
+      dst: dst.clone(),
+    }],
+    dst,
+    raw: resp_json,
+  })
+}
+
+/// v3 签名：sha256(appKey + truncate(q) + salt + curtime + appSecret)，小写 hex
+fn sign_v3(app_key: &str, app_secret: &str, q: &str, salt: &str, curtime: &str) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(app_key);
+  hasher.update(truncate_input(q));
+  hasher.update(salt);
+  hasher.update(curtime);
+  hasher.update(app_secret);
+  format!("{:x}", hasher.finalize())
+}
+
+/// 按“字符”而不是字节截断（中文也是一个字符算一个）
+fn truncate_input(q: &str) -> String {
+  let chars: Vec<char> = q.chars().collect();
+  let len = chars.len();
+  if len <= 20 {
+    return q.to_string();
+  }
+
+  let head: String = chars[..10].iter().collect();
+  let tail: String = chars[len - 10..].iter().collect();
+  format!("{head}{len}{tail}")
+}
+
+/// 常见错误码的中文提示，其余直接显示错误码
+fn error_message(code: &str) -> &'static str {
+  match code {
+    "101" => "缺少必填参数",
+    "102" => "不支持的语言类型",
+    "103" => "翻译文本过长",
+    "108" => "应用ID无效，请检查有道 appId",
+    "113" => "翻译文本不能为空",
+    "202" => "签名检验失败，请检查有道 appSecret",
+    "206" => "时间戳无效（请检查本机时间）",
+    "401" => "账户已欠费",
+    "411" => "访问频率受限，请稍后再试",
+    _ => "有道接口返回错误",
+  }
+}
+
+pub(crate) fn get_youdao_keys(pool: &DbPool) -> Result<AppPair, AppError> {
+  let keys = crate::settings::get_api_keys(pool)?
+    .ok_or_else(|| AppError::msg("未配置 API Keys，请先在设置中保存"))?
+    .translation
+    .youdao;
+
+  if keys.app_id.is_empty() || keys.app_secret.is_empty() {
+    return Err(AppError::msg("有道 appId/appSecret 为空，请先在设置里填写"));
+  }
+
+  Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use wiremock::{
+    matchers::{body_string_contains, method, path},
+    Mock, MockServer, ResponseTemplate,
+  };
+
+  fn keys() -> AppPair {
+    AppPair {
+      app_id: "app".to_string(),
+      app_secret: "secret".to_string(),
+    }
+  }
+
+  fn payload(q: &str) -> YoudaoTextPayload {
+    YoudaoTextPayload {
+      q: q.to_string(),
+      from: "auto".to_string(),
+      to: "zh-CHS".to_string(),
+    }
+  }
+
+  #[test]
+  fn sign_matches_v3_spec() {
+    assert_eq!(
+      sign_v3("app", "secret", "hello", "salt", "1700000000"),
+      "7663eff532d251908c402945c0075f8dc5cb21be0d6b13285f7c98acf9bbc6f3"
+    );
+  }
+
+  #[test]
+  fn truncates_long_input_by_chars() {
+    let q = "这是一段用来测试有道翻译签名截断规则的比较长的中文文本内容";
+    assert_eq!(truncate_input(q), "这是一段用来测试有道29比较长的中文文本内容");
+    assert_eq!(truncate_input("short text"), "short text");
+    assert_eq!(
+      sign_v3("app", "secret", q, "salt", "1700000000"),
+      "aea1ad9d1ddb9876e071db75368038a1ff2278f0bb99f476fecfccb9815e6274"
+    );
+  }
+
+  #[tokio::test]
+  async fn maps_successful_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .and(path("/api"))
+      .and(body_string_contains("signType=v3"))
+      .and(body_string_contains("appKey=app"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "errorCode": "0",
+        "query": "hello",
+        "translation": ["你好"],
+        "l": "en2zh-CHS"
+      })))
+      .expect(1)
+      .mount(&server)
+      .await;
+
+    let endpoint = format!("{}/api", server.uri());
+    let result = text_translate(&Client::new(), &endpoint, &keys(), &payload("hello"))
+      .await
+      .unwrap();
+
+    assert_eq!(result.from, "en");
+    assert_eq!(result.to, "zh-CHS");
+    assert_eq!(result.dst, "你好");
+  }
+
+  #[tokio::test]
+  async fn surfaces_error_code() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .respond_with(
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({ "errorCode": "202" })),
+      )
+      .mount(&server)
+      .await;
+
+    let endpoint = format!("{}/api", server.uri());
+    let err = text_translate(&Client::new(), &endpoint, &keys(), &payload("hello"))
+      .await
+      .unwrap_err();
+
+    assert!(err.to_string().contains("202"));
+  }
+}
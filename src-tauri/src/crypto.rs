@@ -0,0 +1,231 @@
+use std::{
+  fs,
+  io::Write,
+  path::Path,
+  sync::OnceLock,
+};
+
+use aes_gcm::{
+  aead::{Aead, AeadCore, KeyInit, OsRng},
+  Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::error::AppError;
+
+/// 本机密钥文件（位于 app_data_dir，和数据库分开存放）
+const KEY_FILE_NAME: &str = "secret.key";
+
+/// 密文前缀：用来区分“已加密”和“历史明文”字段
+const ENC_PREFIX: &str = "enc:v1:";
+
+/// AES-GCM nonce 长度（96 bit）
+const NONCE_LEN: usize = 12;
+
+static SECRET_KEY: OnceLock<Key<Aes256Gcm>> = OnceLock::new();
+
+/// 启动时加载（不存在则生成）本机密钥，必须在读写 API Keys 之前调用
+pub fn init(app_dir: &Path) -> Result<(), AppError> {
+  let key = load_or_create_key(&app_dir.join(KEY_FILE_NAME))?;
+
+  // 重复 init（例如热重载）时保留第一次加载的密钥
+  let _ = SECRET_KEY.set(key);
+  Ok(())
+}
+
+fn load_or_create_key(path: &Path) -> Result<Key<Aes256Gcm>, AppError> {
+  let text = if path.exists() {
+    fs::read_to_string(path).map_err(|e| AppError::Io(format!("read secret key failed: {e}")))?
+  } else {
+    String::new()
+  };
+
+  // 空文件：旧版本在创建和写入之间崩溃留下的，里面还没有密钥，按不存在处理
+  let key = if !text.trim().is_empty() {
+    let bytes = general_purpose::STANDARD
+      .decode(text.trim())
+      .map_err(|e| AppError::msg(format!("secret key file is corrupted: {e}")))?;
+    if bytes.len() != 32 {
+      return Err(AppError::msg("secret key file is corrupted: invalid key length"));
+    }
+    *Key::<Aes256Gcm>::from_slice(&bytes)
+  } else {
+    let key = Aes256Gcm::generate_key(OsRng);
+    write_key_file(path, &general_purpose::STANDARD.encode(key))?;
+    key
+  };
+
+  Ok(key)
+}
+
+/// 是否为本模块产生的密文
+pub fn is_encrypted(value: &str) -> bool {
+  value.starts_with(ENC_PREFIX)
+}
+
+/// 加密单个字段：输出 `enc:v1:<base64(nonce || ciphertext)>`
+pub fn encrypt(plain: &str) -> Result<String, AppError> {
+  let cipher = Aes256Gcm::new(secret_key()?);
+  let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+  let ciphertext = cipher
+    .encrypt(&nonce, plain.as_bytes())
+    .map_err(|_| AppError::msg("encrypt secret failed"))?;
+
+  let mut buf = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+  buf.extend_from_slice(&nonce);
+  buf.extend_from_slice(&ciphertext);
+
+  Ok(format!("{ENC_PREFIX}{}", general_purpose::STANDARD.encode(buf)))
+}
+
+/// 解密单个字段；没有密文前缀的值视为历史明文，原样返回
+pub fn decrypt(value: &str) -> Result<String, AppError> {
+  let Some(encoded) = value.strip_prefix(ENC_PREFIX) else {
+    return Ok(value.to_string());
+  };
+
+  let buf = general_purpose::STANDARD
+    .decode(encoded)
+    .map_err(|e| AppError::msg(format!("decrypt secret failed: {e}")))?;
+  if buf.len() < NONCE_LEN {
+    return Err(AppError::msg("decrypt secret failed: ciphertext too short"));
+  }
+
+  let (nonce, ciphertext) = buf.split_at(NONCE_LEN);
+  let cipher = Aes256Gcm::new(secret_key()?);
+  let plain = cipher
+    .decrypt(Nonce::from_slice(nonce), ciphertext)
+    // 密钥文件被替换/丢失时会走到这里
+    .map_err(|_| AppError::msg("decrypt secret failed: 本机密钥不匹配，请重新填写 API Keys"))?;
+
+  String::from_utf8(plain).map_err(|e| AppError::msg(format!("decrypt secret failed: {e}")))
+}
+
+fn secret_key() -> Result<&'static Key<Aes256Gcm>, AppError> {
+  SECRET_KEY
+    .get()
+    .ok_or_else(|| AppError::msg("secret key is not initialized"))
+}
+
+/// 先写临时文件再 rename：中途崩溃不会留下不完整的密钥文件
+fn write_key_file(path: &Path, content: &str) -> Result<(), AppError> {
+  let tmp = path.with_extension("key.tmp");
+  let mut opts = fs::OpenOptions::new();
+  opts.write(true).create(true).truncate(true);
+
+  // 仅当前用户可读写
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    opts.mode(0o600);
+  }
+
+  let mut file = opts
+    .open(&tmp)
+    .map_err(|e| AppError::Io(format!("create secret key failed: {e}")))?;
+  file
+    .write_all(content.as_bytes())
+    .and_then(|_| file.sync_all())
+    .map_err(|e| AppError::Io(format!("write secret key failed: {e}")))?;
+  drop(file);
+
+  fs::rename(&tmp, path).map_err(|e| AppError::Io(format!("save secret key failed: {e}")))?;
+
+  Ok(())
+}
+
+/// 测试用：不落盘，直接设置一个固定密钥（全局只能设置一次，所有测试共用）
+#[cfg(test)]
+pub(crate) fn init_for_test() {
+  SECRET_KEY.get_or_init(|| *Key::<Aes256Gcm>::from_slice(&[7u8; 32]));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("crypto-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn round_trip() {
+    init_for_test();
+
+    let enc = encrypt("sk-123 密钥").unwrap();
+    assert!(is_encrypted(&enc));
+    assert_eq!(decrypt(&enc).unwrap(), "sk-123 密钥");
+    // 每次使用随机 nonce
+    assert_ne!(encrypt("sk-123 密钥").unwrap(), enc);
+
+    // 以前缀开头的明文加密后也能原样解出来
+    let tricky = format!("{ENC_PREFIX}not-really");
+    assert_eq!(decrypt(&encrypt(&tricky).unwrap()).unwrap(), tricky);
+
+    // 历史明文原样返回
+    assert_eq!(decrypt("plain").unwrap(), "plain");
+  }
+
+  #[test]
+  fn rejects_tampered_ciphertext() {
+    init_for_test();
+
+    let enc = encrypt("secret").unwrap();
+    let mut buf = general_purpose::STANDARD.decode(&enc[ENC_PREFIX.len()..]).unwrap();
+    *buf.last_mut().unwrap() ^= 1;
+    let tampered = format!("{ENC_PREFIX}{}", general_purpose::STANDARD.encode(buf));
+    assert!(decrypt(&tampered).is_err());
+
+    assert!(decrypt(&format!("{ENC_PREFIX}%%%")).is_err());
+    assert!(decrypt(&format!("{ENC_PREFIX}AAAA")).is_err());
+  }
+
+  #[test]
+  fn key_file_is_created_and_reloaded() {
+    let dir = temp_dir("reload");
+    let path = dir.join(KEY_FILE_NAME);
+
+    let key = load_or_create_key(&path).unwrap();
+    assert!(path.exists());
+    assert!(!path.with_extension("key.tmp").exists());
+    assert_eq!(load_or_create_key(&path).unwrap(), key);
+
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn empty_key_file_is_regenerated() {
+    let dir = temp_dir("empty");
+    let path = dir.join(KEY_FILE_NAME);
+    fs::write(&path, "").unwrap();
+
+    let key = load_or_create_key(&path).unwrap();
+    assert!(!fs::read_to_string(&path).unwrap().trim().is_empty());
+    assert_eq!(load_or_create_key(&path).unwrap(), key);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn corrupted_key_file_is_error() {
+    let dir = temp_dir("corrupted");
+    let path = dir.join(KEY_FILE_NAME);
+
+    fs::write(&path, "not base64!").unwrap();
+    assert!(load_or_create_key(&path).is_err());
+    fs::write(&path, general_purpose::STANDARD.encode([1u8; 16])).unwrap();
+    assert!(load_or_create_key(&path).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -2,21 +2,304 @@ use rusqlite::Connection;
 
 use crate::error::AppError;
 
-pub fn migrate(conn: &Connection) -> Result<(), AppError> {
-  conn
-    .execute_batch(
-      r#"
-      PRAGMA journal_mode = WAL;
-      PRAGMA foreign_keys = ON;
+/// 单个迁移步骤：
+/// - version 从 1 开始连续递增，已发布的步骤禁止修改，只能追加新步骤
+/// - sql 在事务内执行，失败会整体回滚，user_version 不会前进
+struct Migration {
+  version: i64,
+  name: &'static str,
+  sql: &'static str,
+}
 
+/// 按版本顺序排列的全部迁移（新增表/字段时在末尾追加）
+const MIGRATIONS: &[Migration] = &[
+  Migration {
+    version: 1,
+    name: "create app_settings",
+    // 老版本数据库没有记录 user_version，但表已经存在，所以这里保留 IF NOT EXISTS
+    sql: r#"
       CREATE TABLE IF NOT EXISTS app_settings (
         key TEXT PRIMARY KEY,
         value TEXT NOT NULL,
         updated_at INTEGER NOT NULL
       );
       "#,
+  },
+  Migration {
+    version: 2,
+    name: "create translation_history",
+    // FTS5 外部内容表 + 触发器同步；trigram 分词对中文子串搜索更友好
+    sql: r#"
+      CREATE TABLE translation_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        provider TEXT NOT NULL,
+        from_lang TEXT NOT NULL,
+        to_lang TEXT NOT NULL,
+        source TEXT NOT NULL,
+        result TEXT NOT NULL,
+        favorite INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL
+      );
+
+      CREATE INDEX idx_translation_history_created_at ON translation_history(created_at);
+
+      CREATE VIRTUAL TABLE translation_history_fts USING fts5(
+        source,
+        result,
+        content = 'translation_history',
+        content_rowid = 'id',
+        tokenize = 'trigram'
+      );
+
+      CREATE TRIGGER translation_history_ai AFTER INSERT ON translation_history BEGIN
+        INSERT INTO translation_history_fts(rowid, source, result)
+        VALUES (new.id, new.source, new.result);
+      END;
+
+      CREATE TRIGGER translation_history_ad AFTER DELETE ON translation_history BEGIN
+        INSERT INTO translation_history_fts(translation_history_fts, rowid, source, result)
+        VALUES ('delete', old.id, old.source, old.result);
+      END;
+
+      CREATE TRIGGER translation_history_au AFTER UPDATE OF source, result ON translation_history BEGIN
+        INSERT INTO translation_history_fts(translation_history_fts, rowid, source, result)
+        VALUES ('delete', old.id, old.source, old.result);
+        INSERT INTO translation_history_fts(rowid, source, result)
+        VALUES (new.id, new.source, new.result);
+      END;
+      "#,
+  },
+  Migration {
+    version: 3,
+    name: "create translation_cache",
+    // cache_key = sha256(provider/from/to/规范化原文/term_ids)；last_hit_at 用于 LRU 淘汰
+    sql: r#"
+      CREATE TABLE translation_cache (
+        cache_key TEXT PRIMARY KEY,
+        provider TEXT NOT NULL,
+        from_lang TEXT NOT NULL,
+        to_lang TEXT NOT NULL,
+        source_chars INTEGER NOT NULL,
+        result TEXT NOT NULL,
+        hit_count INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        last_hit_at INTEGER NOT NULL
+      );
+
+      CREATE INDEX idx_translation_cache_last_hit_at ON translation_cache(last_hit_at);
+      "#,
+  },
+  Migration {
+    version: 4,
+    name: "create baidu_access_token",
+    // 只保存当前这组密钥的 token；key_hash = sha256(api_key/app_secret)，access_token 加密存储
+    sql: r#"
+      CREATE TABLE baidu_access_token (
+        key_hash TEXT PRIMARY KEY,
+        access_token TEXT NOT NULL,
+        expires_at_unix INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+      );
+      "#,
+  },
+  Migration {
+    version: 5,
+    name: "create glossaries",
+    // 连接池里的连接没有开启 foreign_keys，删除术语表时在代码里一并删除术语
+    sql: r#"
+      CREATE TABLE glossaries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        from_lang TEXT NOT NULL,
+        to_lang TEXT NOT NULL,
+        baidu_term_ids TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+      );
+
+      CREATE TABLE glossary_terms (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        glossary_id INTEGER NOT NULL REFERENCES glossaries(id) ON DELETE CASCADE,
+        source TEXT NOT NULL,
+        target TEXT NOT NULL,
+        UNIQUE (glossary_id, source)
+      );
+      "#,
+  },
+  Migration {
+    version: 6,
+    name: "create doc_translate_jobs",
+    sql: r#"
+      CREATE TABLE doc_translate_jobs (
+        id TEXT PRIMARY KEY,
+        from_lang TEXT NOT NULL,
+        to_lang TEXT NOT NULL,
+        filename TEXT,
+        format TEXT NOT NULL,
+        output_format TEXT,
+        status TEXT NOT NULL,
+        remote_status TEXT,
+        reason TEXT,
+        output_path TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+      );
+
+      CREATE INDEX idx_doc_translate_jobs_created ON doc_translate_jobs(created_at);
+      "#,
+  },
+];
+
+pub fn migrate(conn: &mut Connection) -> Result<(), AppError> {
+  // journal_mode 不能在事务里切换，放在迁移之前单独执行
+  conn
+    .execute_batch(
+      r#"
+      PRAGMA journal_mode = WAL;
+      PRAGMA foreign_keys = ON;
+      "#,
     )
     .map_err(|e| AppError::Db(format!("migration failed: {e}")))?;
 
+  apply_migrations(conn, MIGRATIONS)
+}
+
+/// 当前数据库的 schema 版本（记录在 PRAGMA user_version 中）
+fn schema_version(conn: &Connection) -> Result<i64, AppError> {
+  conn
+    .query_row("PRAGMA user_version", [], |r| r.get(0))
+    .map_err(|e| AppError::Db(format!("read schema version failed: {e}")))
+}
+
+fn apply_migrations(conn: &mut Connection, migrations: &[Migration]) -> Result<(), AppError> {
+  let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+  let current = schema_version(conn)?;
+
+  // 数据库由更新版本的程序创建：继续运行可能破坏数据，直接拒绝
+  if current > latest {
+    return Err(AppError::Db(format!(
+      "database schema version {current} is newer than supported version {latest}, please upgrade the app"
+    )));
+  }
+
+  for m in migrations.iter().filter(|m| m.version > current) {
+    let tx = conn
+      .transaction()
+      .map_err(|e| AppError::Db(format!("migration {} begin failed: {e}", m.version)))?;
+
+    tx.execute_batch(m.sql)
+      .map_err(|e| AppError::Db(format!("migration {} ({}) failed: {e}", m.version, m.name)))?;
+
+    // PRAGMA 不支持参数绑定；version 是编译期常量，直接拼接是安全的
+    tx.execute_batch(&format!("PRAGMA user_version = {}", m.version))
+      .map_err(|e| AppError::Db(format!("migration {} set version failed: {e}", m.version)))?;
+
+    tx.commit()
+      .map_err(|e| AppError::Db(format!("migration {} commit failed: {e}", m.version)))?;
+
+    log::info!("db migrated to version {} ({})", m.version, m.name);
+  }
+
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn table_exists(conn: &Connection, name: &str) -> bool {
+    conn
+      .query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [name],
+        |r| r.get::<_, i64>(0),
+      )
+      .unwrap()
+      > 0
+  }
+
+  #[test]
+  fn migrates_fresh_database_to_latest() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn).unwrap();
+
+    assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+    assert!(table_exists(&conn, "app_settings"));
+  }
+
+  #[test]
+  fn migrate_is_idempotent() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn).unwrap();
+    migrate(&mut conn).unwrap();
+
+    assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+  }
+
+  #[test]
+  fn versions_are_sequential() {
+    for (i, m) in MIGRATIONS.iter().enumerate() {
+      assert_eq!(m.version, i as i64 + 1, "migration `{}` is out of order", m.name);
+    }
+  }
+
+  #[test]
+  fn keeps_data_of_legacy_unversioned_database() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch(
+        r#"
+        CREATE TABLE app_settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at INTEGER NOT NULL);
+        INSERT INTO app_settings VALUES ('api_keys', '{}', 1);
+        "#,
+      )
+      .unwrap();
+
+    migrate(&mut conn).unwrap();
+
+    let value: String = conn
+      .query_row("SELECT value FROM app_settings WHERE key = 'api_keys'", [], |r| r.get(0))
+      .unwrap();
+    assert_eq!(value, "{}");
+  }
+
+  #[test]
+  fn refuses_database_newer_than_binary() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA user_version = 9999").unwrap();
+
+    assert!(migrate(&mut conn).is_err());
+    assert_eq!(schema_version(&conn).unwrap(), 9999);
+  }
+
+  #[test]
+  fn failed_step_rolls_back_and_keeps_version() {
+    let steps = [
+      Migration { version: 1, name: "ok", sql: "CREATE TABLE a (id INTEGER);" },
+      Migration { version: 2, name: "broken", sql: "CREATE TABLE b (id INTEGER); NOT VALID SQL;" },
+    ];
+    let mut conn = Connection::open_in_memory().unwrap();
+
+    assert!(apply_migrations(&mut conn, &steps).is_err());
+    assert_eq!(schema_version(&conn).unwrap(), 1);
+    assert!(table_exists(&conn, "a"));
+    assert!(!table_exists(&conn, "b"));
+  }
+
+  #[test]
+  fn applies_only_pending_steps_in_order() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    let first = [Migration { version: 1, name: "a", sql: "CREATE TABLE a (id INTEGER);" }];
+    apply_migrations(&mut conn, &first).unwrap();
+
+    // 版本 1 若被重复执行会因表已存在而失败
+    let all = [
+      Migration { version: 1, name: "a", sql: "CREATE TABLE a (id INTEGER);" },
+      Migration { version: 2, name: "b", sql: "ALTER TABLE a ADD COLUMN name TEXT;" },
+    ];
+    apply_migrations(&mut conn, &all).unwrap();
+
+    assert_eq!(schema_version(&conn).unwrap(), 2);
+    conn.execute("INSERT INTO a (id, name) VALUES (1, 'x')", []).unwrap();
+  }
+}
@@ -15,6 +15,9 @@ pub fn init_db(app: &AppHandle) -> Result<DbPool, AppError> {
   std::fs::create_dir_all(&app_dir)
     .map_err(|e| AppError::Io(format!("create app_data_dir failed: {e}")))?;
 
+  // 本机密钥：settings 里的敏感字段依赖它加解密
+  crate::crypto::init(&app_dir)?;
+
   let db_path = app_dir.join("adui_tools.db");
   let manager = SqliteConnectionManager::file(db_path);
 
@@ -25,10 +28,12 @@ pub fn init_db(app: &AppHandle) -> Result<DbPool, AppError> {
 
   // migration
   {
-    let conn = pool.get().map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
-    crate::db::migrate::migrate(&conn)?;
+    let mut conn = pool.get().map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+    crate::db::migrate::migrate(&mut conn)?;
   }
 
+  crate::settings::encrypt_legacy_api_keys(&pool)?;
+
   Ok(pool)
 }
 
@@ -0,0 +1,815 @@
+use std::{
+  collections::HashSet,
+  path::{Path, PathBuf},
+  sync::Mutex,
+  time::Duration,
+};
+
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+  commands::baidu_translate::{self, BaiduTokenState, DocOutputFile, DocTaskInfo},
+  db::DbPool,
+  error::AppError,
+  http::HttpClientState,
+  settings::ProxyService,
+};
+
+/// 任务状态每次变化都会发这个事件，payload 是完整的 DocJob
+pub const DOC_PROGRESS_EVENT: &str = "doc-translate:progress";
+
+/// 轮询间隔：从 3 秒开始每次 ×1.5，最长 60 秒（大文档可能要翻十几分钟）
+const POLL_INITIAL_DELAY: Duration = Duration::from_secs(3);
+const POLL_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// 连续出错（网络、接口报错、下载失败）达到这个次数就把任务标记为失败
+const MAX_CONSECUTIVE_ERRORS: u32 = 5;
+
+/// 本地任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocJobStatus {
+  /// 服务端还在翻译，后台轮询中
+  Running,
+  /// 服务端已完成，正在下载译文
+  Downloading,
+  /// 译文已下载到 output_path
+  Succeeded,
+  Failed,
+}
+
+impl DocJobStatus {
+  fn as_str(self) -> &'static str {
+    match self {
+      DocJobStatus::Running => "running",
+      DocJobStatus::Downloading => "downloading",
+      DocJobStatus::Succeeded => "succeeded",
+      DocJobStatus::Failed => "failed",
+    }
+  }
+
+  fn parse(s: &str) -> Self {
+    match s {
+      "downloading" => DocJobStatus::Downloading,
+      "succeeded" => DocJobStatus::Succeeded,
+      "failed" => DocJobStatus::Failed,
+      _ => DocJobStatus::Running,
+    }
+  }
+
+  pub fn is_finished(self) -> bool {
+    matches!(self, DocJobStatus::Succeeded | DocJobStatus::Failed)
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocJob {
+  /// 百度返回的任务 ID
+  pub id: String,
+  pub from: String,
+  pub to: String,
+  pub filename: Option<String>,
+  pub format: String,
+  pub output_format: Option<String>,
+  pub status: DocJobStatus,
+  /// 服务端最近一次返回的状态（NotStarted / Running / Succeeded ...）
+  pub remote_status: Option<String>,
+  /// 失败原因
+  pub reason: Option<String>,
+  /// 下载到本地的译文路径
+  pub output_path: Option<String>,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+impl DocJob {
+  pub fn new(
+    id: String,
+    from: &str,
+    to: &str,
+    filename: Option<String>,
+    format: &str,
+    output_format: Option<String>,
+  ) -> Self {
+    let now = Utc::now().timestamp();
+    Self {
+      id,
+      from: from.to_string(),
+      to: to.to_string(),
+      filename,
+      format: format.to_string(),
+      output_format,
+      status: DocJobStatus::Running,
+      remote_status: None,
+      reason: None,
+      output_path: None,
+      created_at: now,
+      updated_at: now,
+    }
+  }
+}
+
+/// 正在轮询的任务 ID：同一个任务只跑一个后台 task
+#[derive(Default)]
+pub struct DocJobState {
+  polling: Mutex<HashSet<String>>,
+}
+
+// ======= 存储 =======
+
+pub fn insert(pool: &DbPool, job: &DocJob) -> Result<(), AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute(
+      r#"
+      INSERT INTO doc_translate_jobs(
+        id, from_lang, to_lang, filename, format, output_format,
+        status, remote_status, reason, output_path, created_at, updated_at
+      )
+      VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+      "#,
+      params![
+        job.id,
+        job.from,
+        job.to,
+        job.filename,
+        job.format,
+        job.output_format,
+        job.status.as_str(),
+        job.remote_status,
+        job.reason,
+        job.output_path,
+        job.created_at,
+        job.updated_at
+      ],
+    )
+    .map_err(|e| AppError::Db(format!("insert doc job failed: {e}")))?;
+
+  Ok(())
+}
+
+pub fn get(pool: &DbPool, id: &str) -> Result<Option<DocJob>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .query_row(&format!("{SELECT_JOB} WHERE id = ?1"), params![id], map_job)
+    .optional()
+    .map_err(|e| AppError::Db(format!("query doc job failed: {e}")))
+}
+
+/// 按创建时间倒序
+pub fn list(pool: &DbPool) -> Result<Vec<DocJob>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let mut stmt = conn
+    .prepare(&format!("{SELECT_JOB} ORDER BY created_at DESC"))
+    .map_err(|e| AppError::Db(format!("prepare failed: {e}")))?;
+
+  stmt
+    .query_map([], map_job)
+    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+    .map_err(|e| AppError::Db(format!("query doc jobs failed: {e}")))
+}
+
+/// 只删除任务记录，已下载的文件保留；正在轮询的任务会在下一轮发现记录没了后退出
+pub fn delete(pool: &DbPool, id: &str) -> Result<(), AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute("DELETE FROM doc_translate_jobs WHERE id = ?1", params![id])
+    .map_err(|e| AppError::Db(format!("delete doc job failed: {e}")))?;
+
+  Ok(())
+}
+
+fn save(pool: &DbPool, job: &mut DocJob) -> Result<(), AppError> {
+  job.updated_at = Utc::now().timestamp();
+
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute(
+      r#"
+      UPDATE doc_translate_jobs
+      SET status = ?2, remote_status = ?3, reason = ?4, output_path = ?5, updated_at = ?6
+      WHERE id = ?1
+      "#,
+      params![
+        job.id,
+        job.status.as_str(),
+        job.remote_status,
+        job.reason,
+        job.output_path,
+        job.updated_at
+      ],
+    )
+    .map_err(|e| AppError::Db(format!("update doc job failed: {e}")))?;
+
+  Ok(())
+}
+
+fn unfinished_ids(pool: &DbPool) -> Result<Vec<String>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let mut stmt = conn
+    .prepare("SELECT id FROM doc_translate_jobs WHERE status IN ('running', 'downloading')")
+    .map_err(|e| AppError::Db(format!("prepare failed: {e}")))?;
+
+  stmt
+    .query_map([], |r| r.get(0))
+    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+    .map_err(|e| AppError::Db(format!("query doc jobs failed: {e}")))
+}
+
+// ======= 后台轮询 =======
+
+/// 启动时调用：继续轮询上次没跑完的任务
+pub fn resume(app: &AppHandle) {
+  let pool = app.state::<DbPool>();
+  match unfinished_ids(&pool) {
+    Ok(ids) => ids.into_iter().for_each(|id| spawn(app, id)),
+    Err(e) => log::warn!("resume doc translate jobs failed: {e}"),
+  }
+}
+
+/// 失败的任务重新开始轮询（服务端任务还在时可以继续下载）
+pub fn retry(app: &AppHandle, id: &str) -> Result<DocJob, AppError> {
+  let pool = app.state::<DbPool>();
+  let mut job = get(&pool, id)?.ok_or_else(|| AppError::msg(format!("文档翻译任务不存在：{id}")))?;
+  if job.status != DocJobStatus::Failed {
+    return Ok(job);
+  }
+
+  job.status = DocJobStatus::Running;
+  job.reason = None;
+  save(&pool, &mut job)?;
+  spawn(app, id.to_string());
+  Ok(job)
+}
+
+/// 为任务起一个后台 task；已经在轮询的任务直接忽略
+pub fn spawn(app: &AppHandle, id: String) {
+  let state = app.state::<DocJobState>();
+  if !state.polling.lock().unwrap().insert(id.clone()) {
+    return;
+  }
+
+  let app = app.clone();
+  tauri::async_runtime::spawn(async move {
+    loop {
+      poll(&app, &id).await;
+
+      // poll 退出后、移除 ID 之前，retry 可能已经把任务改回 running（此时它的 spawn 被忽略）：
+      // 在锁内重新检查状态，需要的话由这个 task 接着轮询，不会出现 running 却没人轮询的任务
+      let state = app.state::<DocJobState>();
+      let mut polling = state.polling.lock().unwrap();
+      if !needs_polling(&app.state::<DbPool>(), &id) {
+        polling.remove(&id);
+        return;
+      }
+    }
+  });
+}
+
+fn needs_polling(pool: &DbPool, id: &str) -> bool {
+  matches!(get(pool, id), Ok(Some(job)) if !job.status.is_finished())
+}
+
+async fn poll(app: &AppHandle, id: &str) {
+  let poller = Poller {
+    pool: &app.state::<DbPool>(),
+    http: &app.state::<HttpClientState>(),
+    token_state: &app.state::<BaiduTokenState>(),
+    query_url: baidu_translate::BAIDU_DOC_QUERY_URL,
+    initial_delay: POLL_INITIAL_DELAY,
+    output_dir: Box::new(|| output_dir(app)),
+    notify: Box::new(|job| {
+      let _ = app.emit(DOC_PROGRESS_EVENT, job.clone());
+    }),
+  };
+  poller.run(id).await;
+}
+
+/// 轮询用到的依赖：正式运行时都来自 AppHandle，测试时换成 mock server 和临时目录
+struct Poller<'a> {
+  pool: &'a DbPool,
+  http: &'a HttpClientState,
+  token_state: &'a BaiduTokenState,
+  /// 百度文档翻译的查询接口
+  query_url: &'a str,
+  initial_delay: Duration,
+  /// 译文保存目录，下载时才解析
+  output_dir: Box<dyn Fn() -> Result<PathBuf, AppError> + Send + Sync + 'a>,
+  /// 任务状态落库后调用（发给前端）
+  notify: Box<dyn Fn(&DocJob) + Send + Sync + 'a>,
+}
+
+impl Poller<'_> {
+  /// 轮询到任务结束（或被删除）为止
+  async fn run(&self, id: &str) {
+    let mut delay = self.initial_delay;
+    let mut errors = 0;
+
+    loop {
+      let mut job = match get(self.pool, id) {
+        Ok(Some(job)) if !job.status.is_finished() => job,
+        // 任务被删除或已经结束
+        Ok(_) => return,
+        Err(e) => {
+          log::warn!("load doc job {id} failed: {e}");
+          return;
+        }
+      };
+
+      match self.step(&mut job).await {
+        Ok(()) => errors = 0,
+        Err(e) => {
+          errors += 1;
+          log::warn!("doc job {id} poll failed ({errors}/{MAX_CONSECUTIVE_ERRORS}): {e}");
+          if errors >= MAX_CONSECUTIVE_ERRORS {
+            job.status = DocJobStatus::Failed;
+            job.reason = Some(e.to_string());
+            self.update(&mut job);
+          }
+        }
+      }
+      if job.status.is_finished() {
+        return;
+      }
+
+      tokio::time::sleep(delay).await;
+      delay = (delay * 3 / 2).min(POLL_MAX_DELAY);
+    }
+  }
+
+  /// 查询一次服务端状态；完成时顺带下载译文
+  async fn step(&self, job: &mut DocJob) -> Result<(), AppError> {
+    let client = self.http.client(self.pool, ProxyService::Baidu).await?;
+
+    let task =
+      baidu_translate::query_doc_task(self.pool, &client, self.token_state, self.query_url, &job.id)
+        .await?;
+    let remote_changed = job.remote_status.as_deref() != Some(task.status.as_str());
+    job.remote_status = Some(task.status.clone());
+
+    match task.status.as_str() {
+      "Succeeded" => {
+        let file = pick_output(job, &task)
+          .ok_or_else(|| AppError::msg("文档翻译已完成，但响应里没有译文文件"))?;
+
+        if job.status != DocJobStatus::Downloading {
+          job.status = DocJobStatus::Downloading;
+          self.update(job);
+        }
+
+        let dir = (self.output_dir)()?;
+        let path = download(&client, file, &dir, &default_filename(job, file)).await?;
+        job.status = DocJobStatus::Succeeded;
+        job.output_path = Some(path.to_string_lossy().into_owned());
+        self.update(job);
+      }
+      "Failed" | "Expired" => {
+        job.status = DocJobStatus::Failed;
+        job.reason = Some(task.reason.clone().unwrap_or_else(|| task.status.clone()));
+        self.update(job);
+      }
+      _ if remote_changed => self.update(job),
+      _ => {}
+    }
+
+    Ok(())
+  }
+
+  /// 落库并通知前端；写库失败只记日志（不影响后续轮询）
+  fn update(&self, job: &mut DocJob) {
+    if let Err(e) = save(self.pool, job) {
+      log::warn!("save doc job {} failed: {e}", job.id);
+    }
+    (self.notify)(job);
+  }
+}
+
+/// 指定了 output_format 时优先取对应格式的文件
+fn pick_output<'a>(job: &DocJob, task: &'a DocTaskInfo) -> Option<&'a DocOutputFile> {
+  let files = &task.output.as_ref()?.files;
+  job
+    .output_format
+    .as_deref()
+    .and_then(|fmt| {
+      files
+        .iter()
+        .find(|f| f.format.as_deref().is_some_and(|f| f.eq_ignore_ascii_case(fmt)))
+    })
+    .or_else(|| files.first())
+}
+
+/// 设置里的输出目录，没设置时用系统下载目录
+fn output_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+  if let Some(dir) = crate::settings::get_doc_output_dir(&app.state::<DbPool>())? {
+    return Ok(PathBuf::from(dir));
+  }
+  app
+    .path()
+    .download_dir()
+    .map_err(|e| AppError::Io(format!("resolve download dir failed: {e}")))
+}
+
+/// 服务端没给文件名时：原文件名 + 译文格式，再不行就用任务 ID
+fn default_filename(job: &DocJob, file: &DocOutputFile) -> String {
+  if let Some(name) = file.filename.as_deref().and_then(file_name) {
+    return name;
+  }
+
+  let ext = file
+    .format
+    .as_deref()
+    .or(job.output_format.as_deref())
+    .unwrap_or(&job.format);
+  let stem = job
+    .filename
+    .as_deref()
+    .and_then(file_name)
+    .and_then(|n| Path::new(&n).file_stem().map(|s| s.to_string_lossy().into_owned()))
+    .unwrap_or_else(|| job.id.clone());
+  format!("{stem}_{}.{ext}", job.to)
+}
+
+/// 只取最后一段，避免服务端返回的名字里带路径
+fn file_name(name: &str) -> Option<String> {
+  Path::new(name)
+    .file_name()
+    .map(|n| n.to_string_lossy().into_owned())
+    .filter(|n| !n.is_empty())
+}
+
+async fn download(
+  client: &reqwest::Client,
+  file: &DocOutputFile,
+  dir: &Path,
+  filename: &str,
+) -> Result<PathBuf, AppError> {
+  let bytes = client
+    .get(&file.url)
+    .send()
+    .await?
+    .error_for_status()?
+    .bytes()
+    .await?;
+
+  tokio::fs::create_dir_all(dir)
+    .await
+    .map_err(|e| AppError::Io(format!("create dir failed: {e}")))?;
+
+  let path = unique_path(dir, filename);
+  tokio::fs::write(&path, &bytes)
+    .await
+    .map_err(|e| AppError::Io(format!("write {} failed: {e}", path.display())))?;
+
+  Ok(path)
+}
+
+/// 同名文件已存在时追加 " (1)"、" (2)"……
+fn unique_path(dir: &Path, filename: &str) -> PathBuf {
+  let path = dir.join(filename);
+  if !path.exists() {
+    return path;
+  }
+
+  let name = Path::new(filename);
+  let stem = name.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+  let ext = name
+    .extension()
+    .map(|e| format!(".{}", e.to_string_lossy()))
+    .unwrap_or_default();
+
+  (1..)
+    .map(|i| dir.join(format!("{stem} ({i}){ext}")))
+    .find(|p| !p.exists())
+    .unwrap_or(path)
+}
+
+// ======= helpers =======
+
+const SELECT_JOB: &str = r#"
+  SELECT id, from_lang, to_lang, filename, format, output_format,
+    status, remote_status, reason, output_path, created_at, updated_at
+  FROM doc_translate_jobs
+"#;
+
+fn map_job(r: &Row<'_>) -> rusqlite::Result<DocJob> {
+  Ok(DocJob {
+    id: r.get(0)?,
+    from: r.get(1)?,
+    to: r.get(2)?,
+    filename: r.get(3)?,
+    format: r.get(4)?,
+    output_format: r.get(5)?,
+    status: DocJobStatus::parse(&r.get::<_, String>(6)?),
+    remote_status: r.get(7)?,
+    reason: r.get(8)?,
+    output_path: r.get(9)?,
+    created_at: r.get(10)?,
+    updated_at: r.get(11)?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use r2d2_sqlite::SqliteConnectionManager;
+  use serde_json::json;
+  use wiremock::{
+    matchers::{body_partial_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+  };
+
+  fn job(filename: Option<&str>, output_format: Option<&str>) -> DocJob {
+    DocJob::new(
+      "job-1".to_string(),
+      "en",
+      "zh",
+      filename.map(str::to_string),
+      "pdf",
+      output_format.map(str::to_string),
+    )
+  }
+
+  fn file(format: Option<&str>, filename: Option<&str>) -> DocOutputFile {
+    DocOutputFile {
+      format: format.map(str::to_string),
+      filename: filename.map(str::to_string),
+      url: "https://example.com/out".to_string(),
+    }
+  }
+
+  fn task(files: Vec<DocOutputFile>) -> DocTaskInfo {
+    DocTaskInfo {
+      status: "Succeeded".to_string(),
+      reason: None,
+      output: Some(crate::commands::baidu_translate::DocTaskOutput { files }),
+    }
+  }
+
+  /// 内存库只能有一个连接（每个连接都是独立的数据库）；百度密钥按明文写入，decrypt 会原样返回
+  fn pool_with_job(job: &DocJob) -> DbPool {
+    let pool = r2d2::Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory())
+      .unwrap();
+    crate::db::migrate::migrate(&mut pool.get().unwrap()).unwrap();
+
+    let mut keys = crate::settings::ApiKeysForm::default();
+    keys.translation.baidu.api_key = "ak".to_string();
+    keys.translation.baidu.app_secret = "sk".to_string();
+    pool
+      .get()
+      .unwrap()
+      .execute(
+        "INSERT INTO app_settings(key, value, updated_at) VALUES ('api_keys', ?1, 0)",
+        params![serde_json::to_string(&keys).unwrap()],
+      )
+      .unwrap();
+
+    insert(&pool, job).unwrap();
+    pool
+  }
+
+  fn remote(status: &str, extra: serde_json::Value) -> ResponseTemplate {
+    let mut data = json!({ "id": "job-1", "status": status });
+    data.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+    ResponseTemplate::new(200).set_body_json(json!({ "result": { "data": data } }))
+  }
+
+  /// 按顺序各返回一次，最后一个一直返回
+  async fn mount_queries(server: &MockServer, responses: Vec<ResponseTemplate>) {
+    let last = responses.len() - 1;
+    for (i, resp) in responses.into_iter().enumerate() {
+      let mock = Mock::given(method("POST"))
+        .and(path("/query"))
+        .and(body_partial_json(json!({ "id": "job-1" })))
+        .respond_with(resp);
+      let mock = if i == last { mock } else { mock.up_to_n_times(1) };
+      mock.mount(server).await;
+    }
+  }
+
+  /// 跑完一次轮询，返回每次通知时的状态
+  async fn run_poller(server: &MockServer, pool: &DbPool, dir: &Path) -> Vec<DocJobStatus> {
+    let http = HttpClientState::default();
+    let token_state = BaiduTokenState::with_token("ak", "sk", "tok");
+    let query_url = format!("{}/query", server.uri());
+    let events = Mutex::new(Vec::new());
+    let dir = dir.to_path_buf();
+
+    let poller = Poller {
+      pool,
+      http: &http,
+      token_state: &token_state,
+      query_url: &query_url,
+      initial_delay: Duration::from_millis(1),
+      output_dir: Box::new(move || Ok(dir.clone())),
+      notify: Box::new(|job| events.lock().unwrap().push(job.status)),
+    };
+    poller.run("job-1").await;
+    drop(poller);
+
+    events.into_inner().unwrap()
+  }
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("doc-job-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+  }
+
+  #[tokio::test]
+  async fn poll_downloads_when_succeeded() {
+    let server = MockServer::start().await;
+    let file_url = format!("{}/files/out.docx", server.uri());
+    mount_queries(
+      &server,
+      vec![
+        remote("NotStarted", json!({})),
+        remote("Running", json!({})),
+        remote("Running", json!({})),
+        remote(
+          "Succeeded",
+          json!({ "output": { "files": [
+            { "format": "pdf", "url": format!("{}/files/out.pdf", server.uri()) },
+            { "format": "docx", "filename": "../evil/译文.docx", "url": file_url }
+          ] } }),
+        ),
+      ],
+    )
+    .await;
+    Mock::given(method("GET"))
+      .and(path("/files/out.docx"))
+      .respond_with(ResponseTemplate::new(200).set_body_bytes(b"translated".to_vec()))
+      .expect(1)
+      .mount(&server)
+      .await;
+
+    let job = DocJob::new("job-1".into(), "en", "zh", Some("a.pdf".into()), "pdf", Some("docx".into()));
+    let pool = pool_with_job(&job);
+    let dir = temp_dir("succeeded");
+
+    let events = run_poller(&server, &pool, &dir).await;
+    // 远端状态只有变化时才通知：NotStarted、Running，然后下载中、完成
+    assert_eq!(
+      events,
+      [
+        DocJobStatus::Running,
+        DocJobStatus::Running,
+        DocJobStatus::Downloading,
+        DocJobStatus::Succeeded
+      ]
+    );
+
+    let saved = get(&pool, "job-1").unwrap().unwrap();
+    assert_eq!(saved.status, DocJobStatus::Succeeded);
+    assert_eq!(saved.remote_status.as_deref(), Some("Succeeded"));
+    let output = PathBuf::from(saved.output_path.unwrap());
+    assert_eq!(output, dir.join("译文.docx"));
+    assert_eq!(std::fs::read(&output).unwrap(), b"translated");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn poll_marks_remote_failure() {
+    for (status, extra, reason) in [
+      ("Failed", json!({ "reason": "file is encrypted" }), "file is encrypted"),
+      ("Expired", json!({}), "Expired"),
+    ] {
+      let server = MockServer::start().await;
+      mount_queries(&server, vec![remote("Running", json!({})), remote(status, extra)]).await;
+
+      let job = DocJob::new("job-1".into(), "en", "zh", None, "pdf", None);
+      let pool = pool_with_job(&job);
+
+      let events = run_poller(&server, &pool, &temp_dir("failed")).await;
+      assert_eq!(events, [DocJobStatus::Running, DocJobStatus::Failed]);
+
+      let saved = get(&pool, "job-1").unwrap().unwrap();
+      assert_eq!(saved.status, DocJobStatus::Failed);
+      assert_eq!(saved.reason.as_deref(), Some(reason));
+    }
+  }
+
+  #[tokio::test]
+  async fn poll_fails_after_consecutive_errors() {
+    let server = MockServer::start().await;
+    // 出错计数在成功一次后清零：2 次错误 + 1 次成功 + MAX 次错误
+    let error = || ResponseTemplate::new(200).set_body_json(json!({ "error_code": 31005, "error_msg": "boom" }));
+    let mut responses = vec![error(), error(), remote("Running", json!({}))];
+    responses.extend((0..MAX_CONSECUTIVE_ERRORS).map(|_| error()));
+    mount_queries(&server, responses).await;
+
+    let job = DocJob::new("job-1".into(), "en", "zh", None, "pdf", None);
+    let pool = pool_with_job(&job);
+
+    let events = run_poller(&server, &pool, &temp_dir("errors")).await;
+    assert_eq!(events, [DocJobStatus::Running, DocJobStatus::Failed]);
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3 + MAX_CONSECUTIVE_ERRORS as usize);
+
+    let saved = get(&pool, "job-1").unwrap().unwrap();
+    assert_eq!(saved.status, DocJobStatus::Failed);
+    assert!(saved.reason.unwrap().contains("boom"));
+  }
+
+  #[tokio::test]
+  async fn poll_stops_when_job_is_gone_or_finished() {
+    let server = MockServer::start().await;
+    mount_queries(&server, vec![remote("Running", json!({}))]).await;
+
+    let job = DocJob::new("job-1".into(), "en", "zh", None, "pdf", None);
+    let pool = pool_with_job(&job);
+    delete(&pool, "job-1").unwrap();
+    assert!(run_poller(&server, &pool, &temp_dir("gone")).await.is_empty());
+
+    let mut job = DocJob::new("job-1".into(), "en", "zh", None, "pdf", None);
+    job.status = DocJobStatus::Succeeded;
+    let pool = pool_with_job(&job);
+    assert!(run_poller(&server, &pool, &temp_dir("gone")).await.is_empty());
+
+    assert!(server.received_requests().await.unwrap().is_empty());
+  }
+
+  #[test]
+  fn file_name_strips_directories() {
+    assert_eq!(file_name("report.docx").as_deref(), Some("report.docx"));
+    assert_eq!(file_name("a/b/report.docx").as_deref(), Some("report.docx"));
+    assert_eq!(file_name("../../etc/passwd").as_deref(), Some("passwd"));
+    assert_eq!(file_name("/abs/path.pdf").as_deref(), Some("path.pdf"));
+    assert_eq!(file_name(""), None);
+    assert_eq!(file_name(".."), None);
+    assert_eq!(file_name("dir/"), Some("dir".to_string()));
+  }
+
+  #[test]
+  fn default_filename_prefers_server_name() {
+    let j = job(Some("report.pdf"), Some("docx"));
+    assert_eq!(default_filename(&j, &file(Some("docx"), Some("../x/译文.docx"))), "译文.docx");
+  }
+
+  #[test]
+  fn default_filename_falls_back_to_source_name() {
+    let j = job(Some("/home/me/report.pdf"), Some("pdf"));
+    assert_eq!(default_filename(&j, &file(Some("docx"), None)), "report_zh.docx");
+    assert_eq!(default_filename(&j, &file(None, Some(""))), "report_zh.pdf");
+
+    let j = job(None, None);
+    assert_eq!(default_filename(&j, &file(None, None)), "job-1_zh.pdf");
+  }
+
+  #[test]
+  fn pick_output_prefers_requested_format() {
+    let t = task(vec![file(Some("pdf"), Some("a.pdf")), file(Some("DOCX"), Some("a.docx"))]);
+    assert_eq!(pick_output(&job(None, Some("docx")), &t).unwrap().filename.as_deref(), Some("a.docx"));
+    assert_eq!(pick_output(&job(None, Some("xlsx")), &t).unwrap().filename.as_deref(), Some("a.pdf"));
+    assert_eq!(pick_output(&job(None, None), &t).unwrap().filename.as_deref(), Some("a.pdf"));
+
+    assert!(pick_output(&job(None, None), &task(Vec::new())).is_none());
+    let no_output = DocTaskInfo {
+      output: None,
+      ..task(Vec::new())
+    };
+    assert!(pick_output(&job(None, None), &no_output).is_none());
+  }
+
+  #[test]
+  fn unique_path_appends_counter() {
+    let dir = std::env::temp_dir().join(format!("doc-job-unique-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    assert_eq!(unique_path(&dir, "a.docx"), dir.join("a.docx"));
+    std::fs::write(dir.join("a.docx"), b"").unwrap();
+    assert_eq!(unique_path(&dir, "a.docx"), dir.join("a (1).docx"));
+    std::fs::write(dir.join("a (1).docx"), b"").unwrap();
+    assert_eq!(unique_path(&dir, "a.docx"), dir.join("a (2).docx"));
+
+    std::fs::write(dir.join("noext"), b"").unwrap();
+    assert_eq!(unique_path(&dir, "noext"), dir.join("noext (1)"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -23,6 +23,34 @@ pub enum AppError {
   #[error("{0}")]
   Message(String),
 
+  /// 第三方接口的 access token 无效或已过期（需要重新获取）
+  #[error("access token 无效或已过期：{0}")]
+  TokenInvalid(String),
+
+  /// 触发了接口的 QPS / 频率限制
+  #[error("请求过于频繁，请稍后再试：{0}")]
+  RateLimited(String),
+
+  /// 免费额度或账户余额已用完
+  #[error("接口额度已用完：{0}")]
+  QuotaExhausted(String),
+
+  /// 源语言 / 目标语言不受支持
+  #[error("不支持的语言：{0}")]
+  UnsupportedLanguage(String),
+
+  /// 服务未开通或已被关闭（需要到服务商控制台开通）
+  #[error("服务未开通或已关闭：{0}")]
+  ServiceDisabled(String),
+
+  /// 其他第三方接口业务错误（保留原始错误码）
+  #[error("接口返回错误 ({code})：{msg}")]
+  Provider { code: String, msg: String },
+
+  /// 上传文件没通过校验（大小、格式、输出格式组合），在发请求之前就拦下
+  #[error(transparent)]
+  InvalidFile(#[from] UploadError),
+
   /// HTTP 请求错误：reqwest 内部会携带详细信息
   /// 使用 #[from] 以后，你就可以写：
   /// - reqwest 调用后直接用 `?` 自动转成 AppError
@@ -40,6 +68,9 @@ pub enum AppError {
 #[derive(Debug, Serialize)]
 pub struct ErrorPayload {
   pub message: String,
+  /// 可区分的错误类型（例如 "rate_limited"），前端可据此做重试/跳转设置等处理
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub kind: Option<&'static str>,
 }
 
 impl AppError {
@@ -51,6 +82,19 @@ impl AppError {
   pub fn msg<T: Into<String>>(msg: T) -> Self {
     Self::Message(msg.into())
   }
+
+  fn kind(&self) -> Option<&'static str> {
+    match self {
+      Self::TokenInvalid(_) => Some("token_invalid"),
+      Self::RateLimited(_) => Some("rate_limited"),
+      Self::QuotaExhausted(_) => Some("quota_exhausted"),
+      Self::UnsupportedLanguage(_) => Some("unsupported_language"),
+      Self::ServiceDisabled(_) => Some("service_disabled"),
+      Self::Provider { .. } => Some("provider"),
+      Self::InvalidFile(e) => Some(e.kind()),
+      _ => None,
+    }
+  }
 }
 
 /// 关键：把 AppError 序列化为 { message: "..." }
@@ -62,7 +106,58 @@ impl Serialize for AppError {
   {
     ErrorPayload {
       message: self.to_string(),
+      kind: self.kind(),
     }
       .serialize(serializer)
   }
 }
+
+/// 上传文件的校验错误（前端按 kind 区分，见 AppError::kind）
+#[derive(Debug, Error)]
+pub enum UploadError {
+  #[error("缺少文件：请传 path（文件路径）或文件内容")]
+  NoInput,
+
+  #[error("不是文件：{0}")]
+  NotAFile(String),
+
+  #[error("文件是空的：{0}")]
+  Empty(String),
+
+  #[error("文件过大：{path}（{}），上限 {}", human_size(*.size), human_size(*.max))]
+  TooLarge { path: String, size: u64, max: u64 },
+
+  #[error("不支持的格式：{format}（支持 {supported}）")]
+  UnsupportedFormat { format: String, supported: String },
+
+  #[error("无法从文件内容判断格式，请指定 format")]
+  UnknownContent,
+
+  /// 声明的 format 和文件内容对不上（例如把 .pdf 改名成 .docx）
+  #[error("文件内容是 {detected}，与声明的格式 {declared} 不符")]
+  FormatMismatch { declared: String, detected: &'static str },
+
+  #[error("{input} 文档不能输出为 {output}（可选 {allowed}）")]
+  UnsupportedOutput { input: String, output: String, allowed: String },
+}
+
+impl UploadError {
+  pub fn kind(&self) -> &'static str {
+    match self {
+      UploadError::NoInput | UploadError::NotAFile(_) | UploadError::Empty(_) => "invalid_file",
+      UploadError::TooLarge { .. } => "file_too_large",
+      UploadError::UnsupportedFormat { .. } | UploadError::UnknownContent => "unsupported_format",
+      UploadError::FormatMismatch { .. } => "format_mismatch",
+      UploadError::UnsupportedOutput { .. } => "unsupported_output_format",
+    }
+  }
+}
+
+fn human_size(bytes: u64) -> String {
+  const MB: f64 = 1024.0 * 1024.0;
+  if bytes as f64 >= MB {
+    format!("{:.1} MB", bytes as f64 / MB)
+  } else {
+    format!("{:.1} KB", bytes as f64 / 1024.0)
+  }
+}
@@ -0,0 +1,105 @@
+// ======= CSV / TSV 解析 =======
+// 术语表导入只需要两列，这里手写一个够用的解析器：
+// - 双引号包裹的字段里可以有分隔符和换行，"" 表示一个引号
+// - 兼容 UTF-8 BOM 和 \r\n
+
+/// 按文件内容猜分隔符：第一行有 tab 就当 TSV，否则当 CSV
+pub fn detect_delimiter(content: &str) -> char {
+  let first_line = content.lines().next().unwrap_or("");
+  if first_line.contains('\t') {
+    '\t'
+  } else {
+    ','
+  }
+}
+
+/// 解析成若干行；字段两端的空白会被去掉，空行跳过
+pub fn parse(content: &str, delimiter: char) -> Vec<Vec<String>> {
+  let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+  let mut rows = Vec::new();
+  let mut row = Vec::new();
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut chars = content.chars().peekable();
+
+  while let Some(ch) = chars.next() {
+    if in_quotes {
+      match ch {
+        '"' if chars.peek() == Some(&'"') => {
+          chars.next();
+          field.push('"');
+        }
+        '"' => in_quotes = false,
+        c => field.push(c),
+      }
+      continue;
+    }
+
+    match ch {
+      '"' if field.trim().is_empty() => {
+        field.clear();
+        in_quotes = true;
+      }
+      '\r' => {}
+      '\n' => {
+        row.push(std::mem::take(&mut field).trim().to_string());
+        push_row(&mut rows, std::mem::take(&mut row));
+      }
+      c if c == delimiter => row.push(std::mem::take(&mut field).trim().to_string()),
+      c => field.push(c),
+    }
+  }
+  row.push(field.trim().to_string());
+  push_row(&mut rows, row);
+
+  rows
+}
+
+fn push_row(rows: &mut Vec<Vec<String>>, row: Vec<String>) {
+  if row.iter().any(|f| !f.is_empty()) {
+    rows.push(row);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rows(items: &[&[&str]]) -> Vec<Vec<String>> {
+    items
+      .iter()
+      .map(|r| r.iter().map(|s| s.to_string()).collect())
+      .collect()
+  }
+
+  #[test]
+  fn detect_delimiter_by_first_line() {
+    assert_eq!(detect_delimiter("a\tb\nc,d"), '\t');
+    assert_eq!(detect_delimiter("a,b\nc\td"), ',');
+    assert_eq!(detect_delimiter(""), ',');
+  }
+
+  #[test]
+  fn parse_plain_rows() {
+    assert_eq!(
+      parse("\u{feff}source,target\r\n cat , 猫 \r\n\r\n,\ndog,狗", ','),
+      rows(&[&["source", "target"], &["cat", "猫"], &["dog", "狗"]])
+    );
+    assert_eq!(parse("a\tb,c\n", '\t'), rows(&[&["a", "b,c"]]));
+  }
+
+  #[test]
+  fn parse_quoted_fields() {
+    let content = "\"New York, NY\",纽约\n\"say \"\"hi\"\"\",\"line1\nline2\"\n  \"padded\" ,x\n";
+    assert_eq!(
+      parse(content, ','),
+      rows(&[
+        &["New York, NY", "纽约"],
+        &["say \"hi\"", "line1\nline2"],
+        &["padded", "x"],
+      ])
+    );
+    // 字段中间的引号按普通字符处理
+    assert_eq!(parse("5\" disk,软盘", ','), rows(&[&["5\" disk", "软盘"]]));
+  }
+}
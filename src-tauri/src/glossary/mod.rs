@@ -0,0 +1,544 @@
+mod csv;
+
+use std::future::Future;
+
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  commands::baidu_translate::TextTranslateResult, db::DbPool, error::AppError, i18n::placeholder,
+};
+
+/// 导入时识别为表头的第一列内容（小写比较）
+const HEADER_NAMES: &[&str] = &["source", "src", "term", "原文", "术语", "源术语"];
+
+/// 本地术语表：按语言方向（统一语言代码）维护一组 源术语 -> 目标术语
+#[derive(Debug, Clone, Serialize)]
+pub struct Glossary {
+  pub id: i64,
+  pub name: String,
+  pub from: String,
+  pub to: String,
+  /// 百度后台对应的术语库 ID（逗号分隔）；填了之后百度请求直接走服务端术语干预
+  pub baidu_term_ids: Option<String>,
+  pub term_count: i64,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryTerm {
+  pub source: String,
+  pub target: String,
+}
+
+/// 新建 / 修改术语表
+#[derive(Debug, Deserialize)]
+pub struct GlossaryPayload {
+  pub name: String,
+  pub from: String,
+  pub to: String,
+  pub baidu_term_ids: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GlossaryImportResult {
+  /// 写入（新增或覆盖）的术语数
+  pub imported: usize,
+  /// 缺列或为空被跳过的行数
+  pub skipped: usize,
+}
+
+pub fn list(pool: &DbPool) -> Result<Vec<Glossary>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let mut stmt = conn
+    .prepare(&format!("{SELECT_GLOSSARY} ORDER BY g.updated_at DESC, g.id DESC"))
+    .map_err(|e| AppError::Db(format!("prepare failed: {e}")))?;
+
+  stmt
+    .query_map([], map_glossary)
+    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+    .map_err(|e| AppError::Db(format!("query glossaries failed: {e}")))
+}
+
+pub fn get(pool: &DbPool, id: i64) -> Result<Glossary, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .query_row(&format!("{SELECT_GLOSSARY} WHERE g.id = ?1"), params![id], map_glossary)
+    .optional()
+    .map_err(|e| AppError::Db(format!("query glossary failed: {e}")))?
+    .ok_or_else(|| AppError::msg(format!("术语表不存在（id={id}）")))
+}
+
+pub fn create(pool: &DbPool, payload: &GlossaryPayload) -> Result<Glossary, AppError> {
+  validate(payload)?;
+  let now = Utc::now().timestamp();
+
+  let id = {
+    let conn = pool
+      .get()
+      .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+    conn
+      .execute(
+        r#"
+        INSERT INTO glossaries(name, from_lang, to_lang, baidu_term_ids, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+        "#,
+        params![
+          payload.name.trim(),
+          payload.from,
+          payload.to,
+          normalize_term_ids(payload.baidu_term_ids.as_deref()),
+          now
+        ],
+      )
+      .map_err(|e| AppError::Db(format!("insert glossary failed: {e}")))?;
+    conn.last_insert_rowid()
+  };
+
+  get(pool, id)
+}
+
+pub fn update(pool: &DbPool, id: i64, payload: &GlossaryPayload) -> Result<Glossary, AppError> {
+  validate(payload)?;
+
+  let changed = {
+    let conn = pool
+      .get()
+      .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+    conn
+      .execute(
+        r#"
+        UPDATE glossaries
+        SET name = ?2, from_lang = ?3, to_lang = ?4, baidu_term_ids = ?5, updated_at = ?6
+        WHERE id = ?1
+        "#,
+        params![
+          id,
+          payload.name.trim(),
+          payload.from,
+          payload.to,
+          normalize_term_ids(payload.baidu_term_ids.as_deref()),
+          Utc::now().timestamp()
+        ],
+      )
+      .map_err(|e| AppError::Db(format!("update glossary failed: {e}")))?
+  };
+  if changed == 0 {
+    return Err(AppError::msg(format!("术语表不存在（id={id}）")));
+  }
+
+  get(pool, id)
+}
+
+/// 删除术语表及其全部术语（连接池的连接没有开 foreign_keys，不能依赖级联删除）
+pub fn delete(pool: &DbPool, id: i64) -> Result<(), AppError> {
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+  tx.execute("DELETE FROM glossary_terms WHERE glossary_id = ?1", params![id])
+    .map_err(|e| AppError::Db(format!("delete glossary terms failed: {e}")))?;
+  tx.execute("DELETE FROM glossaries WHERE id = ?1", params![id])
+    .map_err(|e| AppError::Db(format!("delete glossary failed: {e}")))?;
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(())
+}
+
+/// 术语按源术语排序
+pub fn terms(pool: &DbPool, glossary_id: i64) -> Result<Vec<GlossaryTerm>, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let mut stmt = conn
+    .prepare("SELECT source, target FROM glossary_terms WHERE glossary_id = ?1 ORDER BY source")
+    .map_err(|e| AppError::Db(format!("prepare failed: {e}")))?;
+
+  stmt
+    .query_map(params![glossary_id], |r| {
+      Ok(GlossaryTerm {
+        source: r.get(0)?,
+        target: r.get(1)?,
+      })
+    })
+    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+    .map_err(|e| AppError::Db(format!("query glossary terms failed: {e}")))
+}
+
+/// 新增术语；源术语已存在时覆盖目标术语
+pub fn upsert_term(pool: &DbPool, glossary_id: i64, term: &GlossaryTerm) -> Result<(), AppError> {
+  let (source, target) = (term.source.trim(), term.target.trim());
+  if source.is_empty() || target.is_empty() {
+    return Err(AppError::msg("源术语和目标术语都不能为空"));
+  }
+  get(pool, glossary_id)?;
+
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+  upsert_in(&tx, glossary_id, source, target)?;
+  touch(&tx, glossary_id)?;
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(())
+}
+
+pub fn delete_term(pool: &DbPool, glossary_id: i64, source: &str) -> Result<(), AppError> {
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+  tx.execute(
+    "DELETE FROM glossary_terms WHERE glossary_id = ?1 AND source = ?2",
+    params![glossary_id, source],
+  )
+  .map_err(|e| AppError::Db(format!("delete glossary term failed: {e}")))?;
+  touch(&tx, glossary_id)?;
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(())
+}
+
+/// 导入 CSV / TSV：每行 源术语,目标术语（多余的列忽略）
+///
+/// delimiter 不传时按内容猜；第一行是表头（source/target、原文/译文，或者就是两列语言代码）时跳过；
+/// replace 为 true 时先清空原有术语
+pub fn import(
+  pool: &DbPool,
+  glossary_id: i64,
+  content: &str,
+  delimiter: Option<char>,
+  replace: bool,
+) -> Result<GlossaryImportResult, AppError> {
+  let glossary = get(pool, glossary_id)?;
+  let delimiter = delimiter.unwrap_or_else(|| csv::detect_delimiter(content));
+  let mut rows = csv::parse(content, delimiter);
+
+  if rows.first().is_some_and(|r| is_header(r, &glossary)) {
+    rows.remove(0);
+  }
+
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+  if replace {
+    tx.execute("DELETE FROM glossary_terms WHERE glossary_id = ?1", params![glossary_id])
+      .map_err(|e| AppError::Db(format!("delete glossary terms failed: {e}")))?;
+  }
+
+  let mut result = GlossaryImportResult {
+    imported: 0,
+    skipped: 0,
+  };
+  for row in &rows {
+    match (row.first(), row.get(1)) {
+      (Some(source), Some(target)) if !source.is_empty() && !target.is_empty() => {
+        upsert_in(&tx, glossary_id, source, target)?;
+        result.imported += 1;
+      }
+      _ => result.skipped += 1,
+    }
+  }
+
+  touch(&tx, glossary_id)?;
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(result)
+}
+
+// ======= 翻译时使用 =======
+
+/// 一次翻译请求选用的术语表
+pub struct SelectedGlossary {
+  pub glossary: Glossary,
+  pub terms: Vec<GlossaryTerm>,
+}
+
+/// 按 id 取术语表，并检查语言方向和本次翻译一致（from=auto 时只检查目标语言）
+pub fn select(pool: &DbPool, id: i64, from: &str, to: &str) -> Result<SelectedGlossary, AppError> {
+  let glossary = get(pool, id)?;
+  if glossary.to != to || (from != "auto" && glossary.from != from) {
+    return Err(AppError::msg(format!(
+      "术语表「{}」的语言方向是 {} → {}，与本次翻译（{from} → {to}）不一致",
+      glossary.name, glossary.from, glossary.to
+    )));
+  }
+
+  let terms = terms(pool, id)?;
+  Ok(SelectedGlossary { glossary, terms })
+}
+
+impl SelectedGlossary {
+  /// 关联的百度术语库 ID；和请求里显式传的 term_ids 合并
+  pub fn baidu_term_ids(&self, explicit: Option<&str>) -> Option<String> {
+    let linked = self.glossary.baidu_term_ids.as_deref()?;
+    Some(match explicit.map(str::trim).filter(|s| !s.is_empty()) {
+      Some(explicit) => format!("{explicit},{linked}"),
+      None => linked.to_string(),
+    })
+  }
+
+  /// 客户端强制术语：原文中的源术语先换成占位记号，译文里再换成目标术语
+  ///
+  /// 服务端把记号弄丢或重复时，退回不带术语表的普通翻译（只记日志，不报错）
+  pub async fn enforce<F, Fut>(&self, text: &str, translate: F) -> Result<TextTranslateResult, AppError>
+  where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<TextTranslateResult, AppError>>,
+  {
+    let pairs: Vec<(String, String)> = self
+      .terms
+      .iter()
+      .map(|t| (t.source.clone(), t.target.clone()))
+      .collect();
+    let protected = placeholder::protect_terms(text, &pairs);
+    if protected.text == text {
+      return translate(text.to_string()).await;
+    }
+
+    let mut result = translate(protected.text.clone()).await?;
+    match protected.restore(&result.dst) {
+      Some(dst) => {
+        result.dst = dst;
+        for segment in &mut result.segments {
+          segment.src = protected.unprotect(&segment.src);
+          segment.dst = protected.restore_lenient(&segment.dst);
+        }
+        Ok(result)
+      }
+      None => {
+        log::warn!(
+          "glossary {} placeholders lost in translation, falling back to plain translation",
+          self.glossary.id
+        );
+        translate(text.to_string()).await
+      }
+    }
+  }
+}
+
+// ======= helpers =======
+
+const SELECT_GLOSSARY: &str = r#"
+  SELECT g.id, g.name, g.from_lang, g.to_lang, g.baidu_term_ids, g.created_at, g.updated_at,
+    (SELECT COUNT(*) FROM glossary_terms t WHERE t.glossary_id = g.id)
+  FROM glossaries g
+"#;
+
+fn map_glossary(r: &Row<'_>) -> rusqlite::Result<Glossary> {
+  Ok(Glossary {
+    id: r.get(0)?,
+    name: r.get(1)?,
+    from: r.get(2)?,
+    to: r.get(3)?,
+    baidu_term_ids: r.get(4)?,
+    created_at: r.get(5)?,
+    updated_at: r.get(6)?,
+    term_count: r.get(7)?,
+  })
+}
+
+fn validate(payload: &GlossaryPayload) -> Result<(), AppError> {
+  if payload.name.trim().is_empty() {
+    return Err(AppError::msg("术语表名称不能为空"));
+  }
+  if payload.from.is_empty() || payload.to.is_empty() || payload.from == "auto" {
+    return Err(AppError::msg("术语表需要指定明确的源语言和目标语言"));
+  }
+  Ok(())
+}
+
+/// "a, b,,c" -> "a,b,c"；全空时存 NULL
+fn normalize_term_ids(term_ids: Option<&str>) -> Option<String> {
+  let ids: Vec<&str> = term_ids?
+    .split(',')
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .collect();
+  (!ids.is_empty()).then(|| ids.join(","))
+}
+
+fn is_header(row: &[String], glossary: &Glossary) -> bool {
+  let first = row.first().map(|s| s.to_lowercase()).unwrap_or_default();
+  HEADER_NAMES.contains(&first.as_str())
+    || (first == glossary.from.to_lowercase()
+      && row.get(1).is_some_and(|s| s.eq_ignore_ascii_case(&glossary.to)))
+}
+
+fn upsert_in(conn: &rusqlite::Connection, glossary_id: i64, source: &str, target: &str) -> Result<(), AppError> {
+  conn
+    .execute(
+      r#"
+      INSERT INTO glossary_terms(glossary_id, source, target) VALUES (?1, ?2, ?3)
+      ON CONFLICT(glossary_id, source) DO UPDATE SET target = excluded.target
+      "#,
+      params![glossary_id, source, target],
+    )
+    .map_err(|e| AppError::Db(format!("upsert glossary term failed: {e}")))?;
+  Ok(())
+}
+
+fn touch(conn: &rusqlite::Connection, glossary_id: i64) -> Result<(), AppError> {
+  conn
+    .execute(
+      "UPDATE glossaries SET updated_at = ?2 WHERE id = ?1",
+      params![glossary_id, Utc::now().timestamp()],
+    )
+    .map_err(|e| AppError::Db(format!("update glossary failed: {e}")))?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex;
+
+  use super::*;
+  use crate::commands::baidu_translate::TranslationSegment;
+
+  fn glossary(terms: &[(&str, &str)]) -> SelectedGlossary {
+    SelectedGlossary {
+      glossary: Glossary {
+        id: 1,
+        name: "test".to_string(),
+        from: "en".to_string(),
+        to: "zh".to_string(),
+        baidu_term_ids: None,
+        term_count: terms.len() as i64,
+        created_at: 0,
+        updated_at: 0,
+      },
+      terms: terms
+        .iter()
+        .map(|(s, t)| GlossaryTerm {
+          source: s.to_string(),
+          target: t.to_string(),
+        })
+        .collect(),
+    }
+  }
+
+  fn result(dst: &str, segments: &[(&str, &str)]) -> TextTranslateResult {
+    TextTranslateResult {
+      from: "en".to_string(),
+      to: "zh".to_string(),
+      dst: dst.to_string(),
+      segments: segments
+        .iter()
+        .map(|(src, dst)| TranslationSegment {
+          src: src.to_string(),
+          dst: dst.to_string(),
+        })
+        .collect(),
+      raw: serde_json::Value::Null,
+    }
+  }
+
+  fn row(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn header_rows() {
+    let g = glossary(&[]).glossary;
+    assert!(is_header(&row(&["Source", "Target"]), &g));
+    assert!(is_header(&row(&["原文", "译文"]), &g));
+    assert!(is_header(&row(&["EN", "zh"]), &g));
+    assert!(!is_header(&row(&["en", "fr"]), &g));
+    assert!(!is_header(&row(&["cat", "猫"]), &g));
+    assert!(!is_header(&[], &g));
+  }
+
+  #[test]
+  fn baidu_term_ids_merges_explicit() {
+    let mut g = glossary(&[]);
+    assert_eq!(g.baidu_term_ids(Some("x")), None);
+
+    g.glossary.baidu_term_ids = Some("a,b".to_string());
+    assert_eq!(g.baidu_term_ids(None).as_deref(), Some("a,b"));
+    assert_eq!(g.baidu_term_ids(Some(" ")).as_deref(), Some("a,b"));
+    assert_eq!(g.baidu_term_ids(Some("x")).as_deref(), Some("x,a,b"));
+  }
+
+  #[tokio::test]
+  async fn enforce_replaces_terms_in_result() {
+    let g = glossary(&[("Rust", "锈语言")]);
+    let sent = Mutex::new(Vec::new());
+
+    let out = g
+      .enforce("I like Rust.", |q| {
+        sent.lock().unwrap().push(q.clone());
+        async move { Ok(result("我喜欢 __PH0__。", &[(&q, "我喜欢 __PH0__。")])) }
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), ["I like __PH0__."]);
+    assert_eq!(out.dst, "我喜欢 锈语言。");
+    assert_eq!(out.segments[0].src, "I like Rust.");
+    assert_eq!(out.segments[0].dst, "我喜欢 锈语言。");
+  }
+
+  #[tokio::test]
+  async fn enforce_falls_back_when_tokens_are_lost() {
+    let g = glossary(&[("Rust", "锈语言")]);
+    let sent = Mutex::new(Vec::new());
+
+    let out = g
+      .enforce("I like Rust.", |q| {
+        sent.lock().unwrap().push(q.clone());
+        let dst = if q.contains("__PH") { "我喜欢。" } else { "我喜欢 Rust。" };
+        async move { Ok(result(dst, &[])) }
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), ["I like __PH0__.", "I like Rust."]);
+    assert_eq!(out.dst, "我喜欢 Rust。");
+  }
+
+  #[tokio::test]
+  async fn enforce_without_matching_terms_sends_text_once() {
+    let g = glossary(&[("Go", "围棋")]);
+    let sent = Mutex::new(Vec::new());
+
+    let out = g
+      .enforce("Google it", |q| {
+        sent.lock().unwrap().push(q.clone());
+        async move { Ok(result("谷歌一下", &[])) }
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), ["Google it"]);
+    assert_eq!(out.dst, "谷歌一下");
+  }
+}
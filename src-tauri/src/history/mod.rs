@@ -0,0 +1,349 @@
+use chrono::Utc;
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::{commands::baidu_translate::TextTranslateResult, db::DbPool, error::AppError};
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// trigram 分词至少需要 3 个字符，更短的关键词退化成 LIKE 查询
+const FTS_MIN_QUERY_CHARS: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+  pub id: i64,
+  pub provider: String,
+  pub from: String,
+  pub to: String,
+  pub source: String,
+  pub result: String,
+  pub favorite: bool,
+  pub created_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryPage {
+  pub items: Vec<HistoryEntry>,
+  pub total: i64,
+  pub page: u32,
+  pub page_size: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+  /// 从 1 开始
+  pub page: Option<u32>,
+  pub page_size: Option<u32>,
+  /// 只看收藏
+  pub favorite_only: Option<bool>,
+  /// 全文搜索关键词（原文和译文都会匹配）
+  pub keyword: Option<String>,
+}
+
+/// 记录一次成功的翻译
+pub fn record(
+  pool: &DbPool,
+  provider: &str,
+  from: &str,
+  to: &str,
+  source: &str,
+  result: &str,
+) -> Result<i64, AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute(
+      r#"
+      INSERT INTO translation_history(provider, from_lang, to_lang, source, result, created_at)
+      VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+      "#,
+      params![provider, from, to, source, result, Utc::now().timestamp()],
+    )
+    .map_err(|e| AppError::Db(format!("insert history failed: {e}")))?;
+
+  Ok(conn.last_insert_rowid())
+}
+
+/// 翻译命令里调用：写历史失败只记日志，不影响翻译结果
+pub fn record_quietly(pool: &DbPool, provider: &str, source: &str, result: &TextTranslateResult) {
+  if let Err(e) = record(pool, provider, &result.from, &result.to, source, &result.dst) {
+    log::warn!("record translation history failed: {e}");
+  }
+}
+
+/// 分页查询（按时间倒序），带 keyword 时走全文搜索
+pub fn list(pool: &DbPool, query: &HistoryQuery) -> Result<HistoryPage, AppError> {
+  let page = query.page.unwrap_or(1).max(1);
+  let page_size = query
+    .page_size
+    .unwrap_or(DEFAULT_PAGE_SIZE)
+    .clamp(1, MAX_PAGE_SIZE);
+  let offset = (page - 1) as i64 * page_size as i64;
+  let favorite_only = query.favorite_only.unwrap_or(false);
+  let keyword = query.keyword.as_deref().map(str::trim).unwrap_or("");
+
+  // ?1 = favorite_only，?2 = 关键词；每个分支都要引用 ?2，否则参数个数对不上
+  let (filter, pattern) = if keyword.is_empty() {
+    ("?2 = ''", String::new())
+  } else if keyword.chars().count() >= FTS_MIN_QUERY_CHARS {
+    (
+      "h.id IN (SELECT rowid FROM translation_history_fts WHERE translation_history_fts MATCH ?2)",
+      fts_phrase(keyword),
+    )
+  } else {
+    (
+      "(h.source LIKE ?2 ESCAPE '\\' OR h.result LIKE ?2 ESCAPE '\\')",
+      format!("%{}%", escape_like(keyword)),
+    )
+  };
+  let where_sql = format!("WHERE (?1 = 0 OR h.favorite = 1) AND {filter}");
+
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let total: i64 = conn
+    .query_row(
+      &format!("SELECT COUNT(*) FROM translation_history h {where_sql}"),
+      params![favorite_only, pattern],
+      |r| r.get(0),
+    )
+    .map_err(|e| AppError::Db(format!("count history failed: {e}")))?;
+
+  let mut stmt = conn
+    .prepare(&format!(
+      r#"
+      SELECT h.id, h.provider, h.from_lang, h.to_lang, h.source, h.result, h.favorite, h.created_at
+      FROM translation_history h
+      {where_sql}
+      ORDER BY h.created_at DESC, h.id DESC
+      LIMIT ?3 OFFSET ?4
+      "#
+    ))
+    .map_err(|e| AppError::Db(format!("prepare failed: {e}")))?;
+
+  let items = stmt
+    .query_map(params![favorite_only, pattern, page_size, offset], map_entry)
+    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+    .map_err(|e| AppError::Db(format!("query history failed: {e}")))?;
+
+  Ok(HistoryPage {
+    items,
+    total,
+    page,
+    page_size,
+  })
+}
+
+pub fn set_favorite(pool: &DbPool, id: i64, favorite: bool) -> Result<(), AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute(
+      "UPDATE translation_history SET favorite = ?2 WHERE id = ?1",
+      params![id, favorite],
+    )
+    .map_err(|e| AppError::Db(format!("update history failed: {e}")))?;
+
+  Ok(())
+}
+
+pub fn delete(pool: &DbPool, ids: &[i64]) -> Result<(), AppError> {
+  let mut conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let tx = conn
+    .transaction()
+    .map_err(|e| AppError::Db(format!("begin failed: {e}")))?;
+  for id in ids {
+    tx.execute("DELETE FROM translation_history WHERE id = ?1", params![id])
+      .map_err(|e| AppError::Db(format!("delete history failed: {e}")))?;
+  }
+  tx.commit()
+    .map_err(|e| AppError::Db(format!("commit failed: {e}")))?;
+
+  Ok(())
+}
+
+/// 清空历史；keep_favorites 为 true 时保留收藏
+pub fn clear(pool: &DbPool, keep_favorites: bool) -> Result<(), AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  conn
+    .execute(
+      "DELETE FROM translation_history WHERE ?1 = 0 OR favorite = 0",
+      params![keep_favorites],
+    )
+    .map_err(|e| AppError::Db(format!("clear history failed: {e}")))?;
+
+  Ok(())
+}
+
+fn map_entry(r: &Row<'_>) -> rusqlite::Result<HistoryEntry> {
+  Ok(HistoryEntry {
+    id: r.get(0)?,
+    provider: r.get(1)?,
+    from: r.get(2)?,
+    to: r.get(3)?,
+    source: r.get(4)?,
+    result: r.get(5)?,
+    favorite: r.get(6)?,
+    created_at: r.get(7)?,
+  })
+}
+
+/// 整个关键词作为一个短语匹配，避免用户输入里的 AND/OR/* 被当成 FTS 语法
+fn fts_phrase(keyword: &str) -> String {
+  format!("\"{}\"", keyword.replace('"', "\"\""))
+}
+
+fn escape_like(keyword: &str) -> String {
+  keyword
+    .replace('\\', "\\\\")
+    .replace('%', "\\%")
+    .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use r2d2_sqlite::SqliteConnectionManager;
+
+  /// 内存库只能有一个连接（每个连接都是独立的数据库）
+  fn pool() -> DbPool {
+    let pool = r2d2::Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory())
+      .unwrap();
+    crate::db::migrate::migrate(&mut pool.get().unwrap()).unwrap();
+    pool
+  }
+
+  fn add(pool: &DbPool, source: &str, result: &str) -> i64 {
+    record(pool, "baidu", "en", "zh", source, result).unwrap()
+  }
+
+  fn query(keyword: &str) -> HistoryQuery {
+    HistoryQuery {
+      page: None,
+      page_size: None,
+      favorite_only: None,
+      keyword: Some(keyword.to_string()),
+    }
+  }
+
+  fn sources(page: &HistoryPage) -> Vec<&str> {
+    page.items.iter().map(|e| e.source.as_str()).collect()
+  }
+
+  #[test]
+  fn fts_search_matches_source_and_result() {
+    let pool = pool();
+    add(&pool, "hello world", "你好世界");
+    add(&pool, "good morning", "早上好");
+    add(&pool, "goodbye", "再见了朋友");
+
+    let page = list(&pool, &query("llo wor")).unwrap();
+    assert_eq!(sources(&page), ["hello world"]);
+    assert_eq!(page.total, 1);
+
+    // trigram 可以匹配中文子串
+    assert_eq!(sources(&list(&pool, &query("再见了")).unwrap()), ["goodbye"]);
+    // 两条都包含 "good"，新的在前
+    assert_eq!(sources(&list(&pool, &query("good")).unwrap()), ["goodbye", "good morning"]);
+    // FTS 语法字符当普通文本处理
+    assert_eq!(list(&pool, &query("\"good\" OR *")).unwrap().total, 0);
+
+    // 删除、修改后索引同步
+    let id = add(&pool, "hello again", "再次问好");
+    assert_eq!(list(&pool, &query("hello")).unwrap().total, 2);
+    delete(&pool, &[id]).unwrap();
+    assert_eq!(list(&pool, &query("hello")).unwrap().total, 1);
+  }
+
+  #[test]
+  fn short_keyword_falls_back_to_like() {
+    let pool = pool();
+    add(&pool, "hello", "你好");
+    add(&pool, "100%_done", "完成");
+    add(&pool, "x", "y");
+
+    assert_eq!(sources(&list(&pool, &query("你好")).unwrap()), ["hello"]);
+    assert_eq!(sources(&list(&pool, &query("he")).unwrap()), ["hello"]);
+    // % 和 _ 按字面匹配
+    assert_eq!(sources(&list(&pool, &query("%_")).unwrap()), ["100%_done"]);
+    assert_eq!(list(&pool, &query("_")).unwrap().total, 1);
+    // 空关键词不过滤
+    assert_eq!(list(&pool, &query("  ")).unwrap().total, 3);
+  }
+
+  #[test]
+  fn paginates_newest_first() {
+    let pool = pool();
+    for i in 0..25 {
+      add(&pool, &format!("text {i}"), "译文");
+    }
+
+    let mut q = query("");
+    q.page_size = Some(10);
+    q.page = Some(3);
+    let page = list(&pool, &q).unwrap();
+    assert_eq!(page.total, 25);
+    assert_eq!((page.page, page.page_size), (3, 10));
+    assert_eq!(sources(&page), ["text 4", "text 3", "text 2", "text 1", "text 0"]);
+
+    // 页码、页大小越界时收敛到合法范围
+    q.page = Some(0);
+    q.page_size = Some(1000);
+    let page = list(&pool, &q).unwrap();
+    assert_eq!((page.page, page.page_size), (1, MAX_PAGE_SIZE));
+    assert_eq!(page.items.len(), 25);
+    assert_eq!(page.items[0].source, "text 24");
+
+    q.page = Some(9);
+    q.page_size = None;
+    let page = list(&pool, &q).unwrap();
+    assert_eq!(page.page_size, DEFAULT_PAGE_SIZE);
+    assert!(page.items.is_empty());
+    assert_eq!(page.total, 25);
+  }
+
+  #[test]
+  fn favorites_filter_and_clear() {
+    let pool = pool();
+    let a = add(&pool, "apple pie", "苹果派");
+    add(&pool, "banana", "香蕉");
+    let c = add(&pool, "apple juice", "苹果汁");
+    set_favorite(&pool, a, true).unwrap();
+    set_favorite(&pool, c, true).unwrap();
+    set_favorite(&pool, c, false).unwrap();
+
+    let mut q = query("");
+    q.favorite_only = Some(true);
+    let page = list(&pool, &q).unwrap();
+    assert_eq!(sources(&page), ["apple pie"]);
+    assert!(page.items[0].favorite);
+
+    // 收藏过滤和关键词可以叠加
+    q.keyword = Some("apple".to_string());
+    assert_eq!(list(&pool, &q).unwrap().total, 1);
+
+    clear(&pool, true).unwrap();
+    let page = list(&pool, &query("")).unwrap();
+    assert_eq!(sources(&page), ["apple pie"]);
+    // 被清掉的记录也从全文索引里删除
+    assert_eq!(list(&pool, &query("juice")).unwrap().total, 0);
+
+    clear(&pool, false).unwrap();
+    assert_eq!(list(&pool, &query("")).unwrap().total, 0);
+    assert_eq!(list(&pool, &query("apple")).unwrap().total, 0);
+  }
+}
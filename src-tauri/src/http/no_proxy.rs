@@ -0,0 +1,240 @@
+use std::net::IpAddr;
+
+/// No Proxy 绕过列表：命中的目标主机直连，不走代理
+///
+/// 支持的写法（大小写不敏感，可以用逗号/空白分隔多个）：
+/// - `*`：所有主机都直连
+/// - `example.com`：精确匹配主机名
+/// - `*.example.com`：只匹配子域名（不含 example.com 本身）
+/// - `.example.com`：匹配 example.com 及其所有子域名
+/// - `10.0.0.0/8`、`fd00::/8`：CIDR 网段；单个 IP 等价于 /32 或 /128
+/// - `localhost`：localhost、*.localhost 以及回环地址 127.0.0.0/8、::1
+#[derive(Debug, Clone, Default)]
+pub struct NoProxy {
+  rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+  All,
+  Localhost,
+  Host(String),
+  /// 后缀匹配；include_self 表示是否也匹配域名本身
+  Domain { suffix: String, include_self: bool },
+  Cidr { net: IpAddr, prefix: u8 },
+}
+
+impl NoProxy {
+  pub fn parse<S: AsRef<str>>(items: &[S]) -> Self {
+    let rules = items
+      .iter()
+      .flat_map(|item| {
+        item
+          .as_ref()
+          .split(|c: char| c == ',' || c.is_whitespace())
+          .map(str::to_string)
+          .collect::<Vec<_>>()
+      })
+      .filter_map(|entry| {
+        let rule = Rule::parse(&entry);
+        if rule.is_none() && !entry.trim().is_empty() {
+          log::warn!("ignore invalid no_proxy entry: {entry}");
+        }
+        rule
+      })
+      .collect();
+
+    Self { rules }
+  }
+
+  /// host 为 URL 中的主机部分（IPv6 可以带方括号）
+  pub fn matches(&self, host: &str) -> bool {
+    let host = normalize_host(host);
+    if host.is_empty() {
+      return false;
+    }
+    let ip = host.parse::<IpAddr>().ok();
+
+    self.rules.iter().any(|rule| match rule {
+      Rule::All => true,
+      Rule::Localhost => match ip {
+        Some(ip) => ip.is_loopback(),
+        None => host == "localhost" || host.ends_with(".localhost"),
+      },
+      Rule::Host(h) => *h == host,
+      Rule::Domain { suffix, include_self } => {
+        host
+          .strip_suffix(suffix.as_str())
+          .is_some_and(|rest| rest.ends_with('.'))
+          || (*include_self && host == *suffix)
+      }
+      Rule::Cidr { net, prefix } => ip.is_some_and(|ip| cidr_contains(*net, *prefix, ip)),
+    })
+  }
+}
+
+impl Rule {
+  fn parse(entry: &str) -> Option<Self> {
+    let entry = entry.trim().to_ascii_lowercase();
+    if entry.is_empty() {
+      return None;
+    }
+    if entry == "*" {
+      return Some(Rule::All);
+    }
+    if entry == "localhost" {
+      return Some(Rule::Localhost);
+    }
+
+    if let Some((net, prefix)) = entry.split_once('/') {
+      let net = normalize_host(net).parse::<IpAddr>().ok()?;
+      let prefix = prefix.parse::<u8>().ok()?;
+      let max = if net.is_ipv4() { 32 } else { 128 };
+      return (prefix <= max).then_some(Rule::Cidr { net, prefix });
+    }
+
+    let host = normalize_host(&entry);
+    if let Ok(ip) = host.parse::<IpAddr>() {
+      let prefix = if ip.is_ipv4() { 32 } else { 128 };
+      return Some(Rule::Cidr { net: ip, prefix });
+    }
+
+    if let Some(suffix) = host.strip_prefix("*.") {
+      return (!suffix.is_empty()).then(|| Rule::Domain {
+        suffix: suffix.to_string(),
+        include_self: false,
+      });
+    }
+    if let Some(suffix) = host.strip_prefix('.') {
+      return (!suffix.is_empty()).then(|| Rule::Domain {
+        suffix: suffix.to_string(),
+        include_self: true,
+      });
+    }
+
+    Some(Rule::Host(host))
+  }
+}
+
+/// 去掉 IPv6 方括号、端口和末尾的点，统一小写
+fn normalize_host(host: &str) -> String {
+  let host = host.trim();
+  let host = if let Some(rest) = host.strip_prefix('[') {
+    rest.split(']').next().unwrap_or_default()
+  } else if host.matches(':').count() == 1 {
+    // host:port（IPv6 地址本身有多个冒号，不能按端口截断）
+    host.split(':').next().unwrap_or_default()
+  } else {
+    host
+  };
+  host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn cidr_contains(net: IpAddr, prefix: u8, ip: IpAddr) -> bool {
+  match (net, ip) {
+    (IpAddr::V4(net), IpAddr::V4(ip)) => {
+      let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+      u32::from(net) & mask == u32::from(ip) & mask
+    }
+    (IpAddr::V6(net), IpAddr::V6(ip)) => {
+      let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+      u128::from(net) & mask == u128::from(ip) & mask
+    }
+    // IPv4-mapped IPv6（::ffff:a.b.c.d）按 IPv4 规则再比一次
+    (IpAddr::V4(_), IpAddr::V6(ip)) => ip
+      .to_ipv4_mapped()
+      .is_some_and(|v4| cidr_contains(net, prefix, IpAddr::V4(v4))),
+    (IpAddr::V6(_), IpAddr::V4(_)) => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn np(items: &[&str]) -> NoProxy {
+    NoProxy::parse(items)
+  }
+
+  #[test]
+  fn exact_host_is_case_insensitive_and_ignores_port() {
+    let p = np(&["Intranet.Corp.com"]);
+    assert!(p.matches("intranet.corp.com"));
+    assert!(p.matches("INTRANET.corp.com:8443"));
+    assert!(p.matches("intranet.corp.com."));
+    assert!(!p.matches("a.intranet.corp.com"));
+    assert!(!p.matches("corp.com"));
+  }
+
+  #[test]
+  fn wildcard_matches_only_subdomains() {
+    let p = np(&["*.corp.com"]);
+    assert!(p.matches("git.corp.com"));
+    assert!(p.matches("a.b.corp.com"));
+    assert!(!p.matches("corp.com"));
+    assert!(!p.matches("evilcorp.com"));
+  }
+
+  #[test]
+  fn leading_dot_matches_domain_and_subdomains() {
+    let p = np(&[".corp.com"]);
+    assert!(p.matches("corp.com"));
+    assert!(p.matches("git.corp.com"));
+    assert!(!p.matches("evilcorp.com"));
+  }
+
+  #[test]
+  fn cidr_ranges() {
+    let p = np(&["10.0.0.0/8", "192.168.1.0/24", "fd00::/8"]);
+    assert!(p.matches("10.1.2.3"));
+    assert!(p.matches("192.168.1.200"));
+    assert!(!p.matches("192.168.2.1"));
+    assert!(!p.matches("11.0.0.1"));
+    assert!(p.matches("[fd12::1]"));
+    assert!(!p.matches("[fe80::1]"));
+    assert!(p.matches("::ffff:10.0.0.1"));
+  }
+
+  #[test]
+  fn single_ip_and_zero_prefix() {
+    let p = np(&["172.16.0.5"]);
+    assert!(p.matches("172.16.0.5"));
+    assert!(!p.matches("172.16.0.6"));
+
+    let all_v4 = np(&["0.0.0.0/0"]);
+    assert!(all_v4.matches("8.8.8.8"));
+    assert!(!all_v4.matches("example.com"));
+  }
+
+  #[test]
+  fn localhost_covers_loopback() {
+    let p = np(&["localhost"]);
+    assert!(p.matches("localhost"));
+    assert!(p.matches("api.localhost"));
+    assert!(p.matches("127.0.0.1"));
+    assert!(p.matches("127.8.8.8"));
+    assert!(p.matches("[::1]"));
+    assert!(!p.matches("10.0.0.1"));
+    assert!(!p.matches("mylocalhost"));
+  }
+
+  #[test]
+  fn star_matches_everything() {
+    assert!(np(&["*"]).matches("api.openai.com"));
+  }
+
+  #[test]
+  fn splits_comma_separated_and_skips_invalid() {
+    let p = np(&["a.com, b.com", "  ", "10.0.0.0/99", "bad::ip::/8"]);
+    assert_eq!(p.rules.len(), 2);
+    assert!(p.matches("a.com"));
+    assert!(p.matches("b.com"));
+    assert!(!p.matches("10.0.0.1"));
+  }
+
+  #[test]
+  fn empty_list_matches_nothing() {
+    let p = np(&[]);
+    assert!(!p.matches("localhost"));
+  }
+}
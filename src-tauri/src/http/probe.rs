@@ -0,0 +1,358 @@
+use std::{
+  error::Error as StdError,
+  io,
+  time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{NetworkProxyForm, ProxyMode};
+
+/// 默认探测地址：返回 {"ip": "..."}，顺便拿到出口 IP
+const DEFAULT_PROBE_URL: &str = "https://api.ipify.org?format=json";
+
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 出口 IP 响应体一般很小，超过这个长度就不再读取（探测地址可以由用户随便填）
+const MAX_IP_BODY_LEN: usize = 4096;
+
+// ======= 前端调用参数 / 返回 =======
+
+#[derive(Debug, Deserialize)]
+pub struct NetworkTestPayload {
+  /// 待测试的代理设置（不需要先保存）
+  pub proxy: NetworkProxyForm,
+  /// 探测地址，默认 DEFAULT_PROBE_URL
+  pub url: Option<String>,
+  pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkTestResult {
+  pub ok: bool,
+  pub url: String,
+  pub latency_ms: u64,
+  pub status: Option<u16>,
+  /// 探测地址返回了 IP（ipify / httpbin 等）时才有
+  pub exit_ip: Option<String>,
+  pub error: Option<NetworkTestError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkTestError {
+  pub kind: NetworkErrorKind,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkErrorKind {
+  /// 代理配置本身不合法（地址/协议写错）
+  InvalidConfig,
+  /// 域名解析失败（代理地址或目标地址）
+  Dns,
+  /// 连接被拒绝（代理没启动 / 端口不对）
+  ConnectRefused,
+  /// 代理要求认证或用户名密码错误（407）
+  ProxyAuth,
+  /// TLS 握手 / 证书错误
+  Tls,
+  Timeout,
+  Other,
+}
+
+/// 用给定的代理设置探测一次，任何失败都体现在返回值里而不是 Err
+pub async fn test_network(payload: NetworkTestPayload) -> NetworkTestResult {
+  let url = payload
+    .url
+    .filter(|u| !u.trim().is_empty())
+    .unwrap_or_else(|| DEFAULT_PROBE_URL.to_string());
+  let timeout = payload
+    .timeout_secs
+    .map(Duration::from_secs)
+    .unwrap_or(DEFAULT_PROBE_TIMEOUT);
+
+  let started = Instant::now();
+  let elapsed_ms = |started: Instant| started.elapsed().as_millis() as u64;
+
+  let failed = |kind, message: String, latency_ms, status| NetworkTestResult {
+    ok: false,
+    url: url.clone(),
+    latency_ms,
+    status,
+    exit_ip: None,
+    error: Some(NetworkTestError { kind, message }),
+  };
+
+  // 和正式请求完全一样的构建方式；系统代理按当前设置重新解析
+  let system = match payload.proxy.mode {
+    ProxyMode::System => match super::resolve_system_proxy().await {
+      Ok(info) => Some(info),
+      Err(e) => return failed(NetworkErrorKind::InvalidConfig, e.to_string(), 0, None),
+    },
+    _ => None,
+  };
+  let client = match super::build_client(Some(payload.proxy), system.as_ref()) {
+    Ok(c) => c,
+    Err(e) => return failed(NetworkErrorKind::InvalidConfig, e.to_string(), 0, None),
+  };
+
+  let resp = match client
+    .get(&url)
+    .header(reqwest::header::USER_AGENT, "ADuiTools")
+    .timeout(timeout)
+    .send()
+    .await
+  {
+    Ok(resp) => resp,
+    Err(e) => {
+      return failed(classify_error(&e), error_chain(&e), elapsed_ms(started), None);
+    }
+  };
+
+  let latency_ms = elapsed_ms(started);
+  let status = resp.status();
+
+  if status == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+    return failed(
+      NetworkErrorKind::ProxyAuth,
+      "代理要求认证，请检查用户名和密码".to_string(),
+      latency_ms,
+      Some(status.as_u16()),
+    );
+  }
+
+  let exit_ip = if status.is_success() {
+    read_body_limited(resp, MAX_IP_BODY_LEN)
+      .await
+      .and_then(|body| parse_exit_ip(&body))
+  } else {
+    None
+  };
+
+  NetworkTestResult {
+    ok: status.is_success(),
+    url,
+    latency_ms,
+    status: Some(status.as_u16()),
+    exit_ip,
+    error: None,
+  }
+}
+
+fn classify_error(e: &reqwest::Error) -> NetworkErrorKind {
+  if e.is_timeout() {
+    return NetworkErrorKind::Timeout;
+  }
+  if e.is_builder() {
+    return NetworkErrorKind::InvalidConfig;
+  }
+
+  // 先看底层 io::Error 的 kind，再按错误信息兜底
+  let mut source: Option<&(dyn StdError + 'static)> = e.source();
+  while let Some(err) = source {
+    if let Some(io_err) = err.downcast_ref::<io::Error>() {
+      match io_err.kind() {
+        io::ErrorKind::ConnectionRefused => return NetworkErrorKind::ConnectRefused,
+        io::ErrorKind::TimedOut => return NetworkErrorKind::Timeout,
+        _ => {}
+      }
+    }
+    source = err.source();
+  }
+
+  let text = error_chain(e).to_lowercase();
+  if text.contains("dns error")
+    || text.contains("failed to lookup address")
+    || text.contains("name or service not known")
+    || text.contains("no such host")
+  {
+    NetworkErrorKind::Dns
+  } else if text.contains("connection refused") {
+    NetworkErrorKind::ConnectRefused
+  } else if text.contains("407") || text.contains("proxy authentication") {
+    NetworkErrorKind::ProxyAuth
+  } else if text.contains("certificate")
+    || text.contains("tls")
+    || text.contains("ssl")
+    || text.contains("handshake")
+  {
+    NetworkErrorKind::Tls
+  } else {
+    NetworkErrorKind::Other
+  }
+}
+
+/// reqwest 的 Display 只有最外层信息，把 source 链拼起来方便排查
+fn error_chain(e: &reqwest::Error) -> String {
+  let mut parts = vec![e.to_string()];
+  let mut source = e.source();
+  while let Some(err) = source {
+    parts.push(err.to_string());
+    source = err.source();
+  }
+  parts.join(": ")
+}
+
+/// 按块读取响应体，超过 limit 就放弃，不会把整个大响应下载下来
+async fn read_body_limited(mut resp: reqwest::Response, limit: usize) -> Option<String> {
+  if resp.content_length().is_some_and(|len| len > limit as u64) {
+    return None;
+  }
+
+  let mut body = Vec::new();
+  while let Some(chunk) = resp.chunk().await.ok()? {
+    if body.len() + chunk.len() > limit {
+      return None;
+    }
+    body.extend_from_slice(&chunk);
+  }
+  String::from_utf8(body).ok()
+}
+
+/// 兼容 ipify（{"ip"}）、httpbin（{"origin"}）和纯文本 IP
+fn parse_exit_ip(body: &str) -> Option<String> {
+  if let Ok(v) = serde_json::from_str::<serde_json::Value>(body) {
+    return ["ip", "origin", "query"]
+      .iter()
+      .find_map(|k| v.get(*k).and_then(|x| x.as_str()))
+      .map(|s| s.to_string());
+  }
+
+  let text = body.trim();
+  text.parse::<std::net::IpAddr>().ok().map(|_| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+  fn direct() -> NetworkProxyForm {
+    NetworkProxyForm {
+      mode: ProxyMode::Disable,
+      protocol: None,
+      host: None,
+      port: None,
+      username: None,
+      password: None,
+      no_proxy: None,
+    }
+  }
+
+  async fn probe(url: String, timeout_secs: Option<u64>) -> NetworkTestResult {
+    test_network(NetworkTestPayload {
+      proxy: direct(),
+      url: Some(url),
+      timeout_secs,
+    })
+    .await
+  }
+
+  async fn send_error(url: &str, timeout: Duration) -> reqwest::Error {
+    reqwest::Client::new()
+      .get(url)
+      .timeout(timeout)
+      .send()
+      .await
+      .unwrap_err()
+  }
+
+  #[test]
+  fn parses_exit_ip_formats() {
+    assert_eq!(parse_exit_ip(r#"{"ip":"1.2.3.4"}"#).as_deref(), Some("1.2.3.4"));
+    assert_eq!(parse_exit_ip(r#"{"origin":"5.6.7.8"}"#).as_deref(), Some("5.6.7.8"));
+    assert_eq!(parse_exit_ip(r#"{"query":"::1","status":"success"}"#).as_deref(), Some("::1"));
+    assert_eq!(parse_exit_ip(" 9.9.9.9\n").as_deref(), Some("9.9.9.9"));
+    assert_eq!(parse_exit_ip("2001:db8::1").as_deref(), Some("2001:db8::1"));
+
+    assert_eq!(parse_exit_ip(r#"{"ip":123}"#), None);
+    assert_eq!(parse_exit_ip(r#"{"other":"1.2.3.4"}"#), None);
+    assert_eq!(parse_exit_ip("<html>hello</html>"), None);
+    assert_eq!(parse_exit_ip("999.1.1.1"), None);
+    assert_eq!(parse_exit_ip(""), None);
+  }
+
+  #[tokio::test]
+  async fn classifies_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+      .mount(&server)
+      .await;
+
+    let e = send_error(&server.uri(), Duration::from_millis(100)).await;
+    assert_eq!(classify_error(&e), NetworkErrorKind::Timeout);
+  }
+
+  #[tokio::test]
+  async fn classifies_connection_refused() {
+    // 先占一个端口再释放，确保上面没有服务在监听
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+      .unwrap()
+      .local_addr()
+      .unwrap()
+      .port();
+
+    let e = send_error(&format!("http://127.0.0.1:{port}/"), Duration::from_secs(5)).await;
+    assert_eq!(classify_error(&e), NetworkErrorKind::ConnectRefused);
+  }
+
+  #[tokio::test]
+  async fn classifies_invalid_url() {
+    let e = send_error("not a url", Duration::from_secs(5)).await;
+    assert_eq!(classify_error(&e), NetworkErrorKind::InvalidConfig);
+  }
+
+  #[tokio::test]
+  async fn probe_reads_exit_ip() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "ip": "1.2.3.4" })))
+      .mount(&server)
+      .await;
+
+    let result = probe(server.uri(), None).await;
+    assert!(result.ok);
+    assert_eq!(result.status, Some(200));
+    assert_eq!(result.exit_ip.as_deref(), Some("1.2.3.4"));
+    assert!(result.error.is_none());
+  }
+
+  #[tokio::test]
+  async fn probe_skips_oversized_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .respond_with(ResponseTemplate::new(200).set_body_string("1".repeat(MAX_IP_BODY_LEN + 1)))
+      .mount(&server)
+      .await;
+
+    let result = probe(server.uri(), None).await;
+    assert!(result.ok);
+    assert_eq!(result.exit_ip, None);
+  }
+
+  #[tokio::test]
+  async fn probe_reports_proxy_auth_and_refused() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+      .respond_with(ResponseTemplate::new(407))
+      .mount(&server)
+      .await;
+
+    let result = probe(server.uri(), None).await;
+    assert!(!result.ok);
+    assert_eq!(result.status, Some(407));
+    assert_eq!(result.error.unwrap().kind, NetworkErrorKind::ProxyAuth);
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+      .unwrap()
+      .local_addr()
+      .unwrap()
+      .port();
+    let result = probe(format!("http://127.0.0.1:{port}/"), Some(5)).await;
+    assert!(!result.ok);
+    assert_eq!(result.status, None);
+    assert_eq!(result.error.unwrap().kind, NetworkErrorKind::ConnectRefused);
+  }
+}
@@ -0,0 +1,317 @@
+use std::env;
+
+use serde::Serialize;
+
+/// 系统代理的来源（展示在设置页，方便用户确认到底用的是哪一份配置）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemProxySource {
+  /// HTTP(S)_PROXY / ALL_PROXY / NO_PROXY 环境变量
+  Env,
+  /// GNOME gsettings（org.gnome.system.proxy）
+  Gnome,
+  /// KDE ~/.config/kioslaverc
+  Kde,
+  /// 没有找到可用配置：交给 reqwest 按平台默认行为处理（macOS / Windows 会读系统设置）
+  None,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemProxyInfo {
+  pub source: SystemProxySource,
+  /// 用于 http:// 请求的代理地址（带 scheme）
+  pub http_proxy: Option<String>,
+  /// 用于 https:// 请求的代理地址（带 scheme）
+  pub https_proxy: Option<String>,
+  pub no_proxy: Vec<String>,
+}
+
+impl SystemProxyInfo {
+  fn none() -> Self {
+    Self {
+      source: SystemProxySource::None,
+      http_proxy: None,
+      https_proxy: None,
+      no_proxy: Vec::new(),
+    }
+  }
+
+  fn has_proxy(&self) -> bool {
+    self.http_proxy.is_some() || self.https_proxy.is_some()
+  }
+}
+
+/// 解析当前生效的系统代理：环境变量优先，其次是桌面环境设置（仅 Linux）
+pub fn resolve() -> SystemProxyInfo {
+  if let Some(info) = from_env() {
+    return info;
+  }
+
+  #[cfg(target_os = "linux")]
+  {
+    let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default().to_lowercase();
+    let found = if desktop.contains("kde") {
+      from_kde().or_else(from_gnome)
+    } else {
+      from_gnome().or_else(from_kde)
+    };
+    if let Some(info) = found {
+      return info;
+    }
+  }
+
+  SystemProxyInfo::none()
+}
+
+/* ==================== env ==================== */
+
+fn from_env() -> Option<SystemProxyInfo> {
+  from_vars(|name| env::var(name).ok())
+}
+
+/// get 按名字取环境变量（单独拆出来方便测试）
+fn from_vars(get: impl Fn(&str) -> Option<String>) -> Option<SystemProxyInfo> {
+  let env_any = |names: &[&str]| {
+    names
+      .iter()
+      .filter_map(|n| get(n))
+      .map(|v| v.trim().to_string())
+      .find(|v| !v.is_empty())
+  };
+  let proxy_env = |names: &[&str]| env_any(names).map(|v| with_scheme(&v, "http"));
+
+  let all = proxy_env(&["ALL_PROXY", "all_proxy"]);
+  let info = SystemProxyInfo {
+    source: SystemProxySource::Env,
+    http_proxy: proxy_env(&["HTTP_PROXY", "http_proxy"]).or_else(|| all.clone()),
+    https_proxy: proxy_env(&["HTTPS_PROXY", "https_proxy"]).or(all),
+    no_proxy: env_any(&["NO_PROXY", "no_proxy"])
+      .map(|v| split_list(&v))
+      .unwrap_or_default(),
+  };
+
+  info.has_proxy().then_some(info)
+}
+
+/* ==================== GNOME ==================== */
+
+#[cfg(target_os = "linux")]
+fn from_gnome() -> Option<SystemProxyInfo> {
+  if gsettings("org.gnome.system.proxy", "mode")? != "manual" {
+    return None;
+  }
+
+  let endpoint = |schema: &str, scheme: &str| -> Option<String> {
+    let host = gsettings(schema, "host")?;
+    let port = gsettings(schema, "port")?;
+    (!host.is_empty() && port != "0").then(|| format!("{scheme}://{host}:{port}"))
+  };
+
+  let socks = endpoint("org.gnome.system.proxy.socks", "socks5");
+  let info = SystemProxyInfo {
+    source: SystemProxySource::Gnome,
+    http_proxy: endpoint("org.gnome.system.proxy.http", "http").or_else(|| socks.clone()),
+    https_proxy: endpoint("org.gnome.system.proxy.https", "http").or(socks),
+    no_proxy: gsettings("org.gnome.system.proxy", "ignore-hosts")
+      .map(|v| parse_gvariant_list(&v))
+      .unwrap_or_default(),
+  };
+
+  info.has_proxy().then_some(info)
+}
+
+/// 调用 gsettings 读取一个键（没有安装 gsettings 时返回 None），去掉 GVariant 字符串的引号
+#[cfg(target_os = "linux")]
+fn gsettings(schema: &str, key: &str) -> Option<String> {
+  let output = std::process::Command::new("gsettings")
+    .args(["get", schema, key])
+    .output()
+    .ok()?;
+  if !output.status.success() {
+    return None;
+  }
+
+  let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  Some(value.trim_matches('\'').to_string())
+}
+
+/// `['localhost', '127.0.0.0/8']` / `@as []` -> Vec<String>
+#[cfg(target_os = "linux")]
+fn parse_gvariant_list(value: &str) -> Vec<String> {
+  value
+    .trim_start_matches("@as")
+    .trim()
+    .trim_start_matches('[')
+    .trim_end_matches(']')
+    .split(',')
+    .map(|s| s.trim().trim_matches('\'').to_string())
+    .filter(|s| !s.is_empty())
+    .collect()
+}
+
+/* ==================== KDE ==================== */
+
+#[cfg(target_os = "linux")]
+fn from_kde() -> Option<SystemProxyInfo> {
+  let config_dir = env::var("XDG_CONFIG_HOME")
+    .ok()
+    .filter(|v| !v.is_empty())
+    .map(std::path::PathBuf::from)
+    .or_else(|| env::var("HOME").ok().map(|h| std::path::Path::new(&h).join(".config")))?;
+
+  let content = std::fs::read_to_string(config_dir.join("kioslaverc")).ok()?;
+  parse_kioslaverc(&content)
+}
+
+/// 只处理 ProxyType=1（手动配置）；PAC / 自动探测不在支持范围内
+#[cfg(target_os = "linux")]
+fn parse_kioslaverc(content: &str) -> Option<SystemProxyInfo> {
+  let mut in_section = false;
+  let mut values = std::collections::HashMap::new();
+
+  for line in content.lines().map(str::trim) {
+    if line.starts_with('[') {
+      in_section = line == "[Proxy Settings]";
+      continue;
+    }
+    if !in_section {
+      continue;
+    }
+    if let Some((k, v)) = line.split_once('=') {
+      values.insert(k.trim().to_string(), v.trim().to_string());
+    }
+  }
+
+  if values.get("ProxyType").map(String::as_str) != Some("1") {
+    return None;
+  }
+
+  let endpoint = |key: &str, scheme: &str| -> Option<String> {
+    let v = values.get(key)?.trim();
+    if v.is_empty() {
+      return None;
+    }
+    // 老版本 KDE 写成 "http://host 8080"（端口用空格分隔）
+    let v = match v.rsplit_once(' ') {
+      Some((host, port)) if port.parse::<u16>().is_ok() => format!("{host}:{port}"),
+      _ => v.to_string(),
+    };
+    // KDE 的 socks 写法是 socks://，reqwest 只认 socks5://
+    let v = match v.strip_prefix("socks://") {
+      Some(rest) => format!("socks5://{rest}"),
+      None => v,
+    };
+    Some(with_scheme(&v, scheme))
+  };
+
+  let socks = endpoint("socksProxy", "socks5");
+  let info = SystemProxyInfo {
+    source: SystemProxySource::Kde,
+    http_proxy: endpoint("httpProxy", "http").or_else(|| socks.clone()),
+    https_proxy: endpoint("httpsProxy", "http").or(socks),
+    no_proxy: values
+      .get("NoProxyFor")
+      .map(|v| split_list(v))
+      .unwrap_or_default(),
+  };
+
+  info.has_proxy().then_some(info)
+}
+
+/* ==================== helpers ==================== */
+
+fn split_list(value: &str) -> Vec<String> {
+  value
+    .split(',')
+    .map(|s| s.trim().to_string())
+    .filter(|s| !s.is_empty())
+    .collect()
+}
+
+/// "proxy:3128" -> "http://proxy:3128"
+fn with_scheme(value: &str, default_scheme: &str) -> String {
+  if value.contains("://") {
+    value.to_string()
+  } else {
+    format!("{default_scheme}://{value}")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use super::*;
+
+  fn vars(items: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let map: HashMap<String, String> = items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |name| map.get(name).cloned()
+  }
+
+  #[test]
+  fn env_per_scheme_and_no_proxy() {
+    let info = from_vars(vars(&[
+      ("HTTP_PROXY", "proxy:3128"),
+      ("https_proxy", "http://secure:8443"),
+      ("NO_PROXY", "localhost, .corp.com,,10.0.0.0/8"),
+    ]))
+    .unwrap();
+    assert_eq!(info.source, SystemProxySource::Env);
+    assert_eq!(info.http_proxy.as_deref(), Some("http://proxy:3128"));
+    assert_eq!(info.https_proxy.as_deref(), Some("http://secure:8443"));
+    assert_eq!(info.no_proxy, ["localhost", ".corp.com", "10.0.0.0/8"]);
+  }
+
+  #[test]
+  fn env_all_proxy_is_fallback() {
+    let info = from_vars(vars(&[("all_proxy", "socks5://127.0.0.1:1080"), ("HTTPS_PROXY", " ")])).unwrap();
+    assert_eq!(info.http_proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+    assert_eq!(info.https_proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+
+    let info = from_vars(vars(&[("ALL_PROXY", "a:1"), ("HTTP_PROXY", "b:2")])).unwrap();
+    assert_eq!(info.http_proxy.as_deref(), Some("http://b:2"));
+    assert_eq!(info.https_proxy.as_deref(), Some("http://a:1"));
+  }
+
+  #[test]
+  fn env_without_proxy_is_none() {
+    assert!(from_vars(vars(&[])).is_none());
+    assert!(from_vars(vars(&[("NO_PROXY", "localhost"), ("HTTP_PROXY", "")])).is_none());
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn gvariant_lists() {
+    assert_eq!(
+      parse_gvariant_list("['localhost', '127.0.0.0/8', '::1']"),
+      ["localhost", "127.0.0.0/8", "::1"]
+    );
+    assert!(parse_gvariant_list("@as []").is_empty());
+    assert!(parse_gvariant_list("[]").is_empty());
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn kioslaverc_manual_proxy() {
+    let content = "[General]\nProxyType=0\n\n[Proxy Settings]\nProxyType=1\nhttpProxy=http://proxy 8080\n\
+                   httpsProxy=\nsocksProxy=socks://127.0.0.1:1080\nNoProxyFor=localhost,.corp.com\n";
+    let info = parse_kioslaverc(content).unwrap();
+    assert_eq!(info.source, SystemProxySource::Kde);
+    assert_eq!(info.http_proxy.as_deref(), Some("http://proxy:8080"));
+    // https 没配时退回 socks
+    assert_eq!(info.https_proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+    assert_eq!(info.no_proxy, ["localhost", ".corp.com"]);
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn kioslaverc_ignores_other_modes_and_sections() {
+    assert!(parse_kioslaverc("[Proxy Settings]\nProxyType=2\nhttpProxy=http://p:1\n").is_none());
+    assert!(parse_kioslaverc("[Other]\nProxyType=1\nhttpProxy=http://p:1\n").is_none());
+    assert!(parse_kioslaverc("[Proxy Settings]\nProxyType=1\n").is_none());
+
+    let info = parse_kioslaverc("[Proxy Settings]\nProxyType=1\nhttpProxy=proxy:3128\n").unwrap();
+    assert_eq!(info.http_proxy.as_deref(), Some("http://proxy:3128"));
+    assert_eq!(info.https_proxy, None);
+  }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Value};
+
+use super::{escape_path_segment, LocaleFile};
+use crate::error::AppError;
+
+/// JSON 资源文件：只翻译字符串叶子节点，key 是 JSON Pointer（例如 "/home/title"）
+pub struct JsonFile {
+  root: Value,
+  /// 沿用源文件的缩进（默认两个空格）
+  indent: String,
+}
+
+impl JsonFile {
+  pub fn parse(content: &str) -> Result<Self, AppError> {
+    Ok(Self {
+      root: serde_json::from_str(content)?,
+      indent: detect_indent(content),
+    })
+  }
+}
+
+impl LocaleFile for JsonFile {
+  fn source_entries(&self) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    collect(&self.root, String::new(), &mut out);
+    out
+  }
+
+  fn apply(&mut self, translations: &HashMap<String, String>) {
+    for (pointer, text) in translations {
+      if let Some(Value::String(s)) = self.root.pointer_mut(pointer) {
+        *s = text.clone();
+      }
+    }
+  }
+
+  fn render(&self) -> Result<String, AppError> {
+    let mut buf = Vec::new();
+    let formatter = PrettyFormatter::with_indent(self.indent.as_bytes());
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    self.root.serialize(&mut ser)?;
+
+    let mut out = String::from_utf8(buf).map_err(|e| AppError::Serde(e.to_string()))?;
+    out.push('\n');
+    Ok(out)
+  }
+}
+
+fn collect(value: &Value, path: String, out: &mut Vec<(String, String)>) {
+  match value {
+    Value::String(s) => out.push((path, s.clone())),
+    Value::Array(items) => {
+      for (i, item) in items.iter().enumerate() {
+        collect(item, format!("{path}/{i}"), out);
+      }
+    }
+    Value::Object(map) => {
+      for (k, v) in map {
+        collect(v, format!("{path}/{}", escape_path_segment(k)), out);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// 取第一行缩进作为缩进单位（tab 或若干空格）
+fn detect_indent(content: &str) -> String {
+  content
+    .lines()
+    .skip(1)
+    .map(|l| &l[..l.len() - l.trim_start().len()])
+    .find(|ws| !ws.is_empty())
+    .unwrap_or("  ")
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn entries_use_json_pointer_paths() {
+    let file = JsonFile::parse(r#"{"home": {"title": "Home", "a/b": "x", "n": 1}, "list": ["one", true]}"#).unwrap();
+    assert_eq!(
+      file.source_entries(),
+      [
+        ("/home/title".to_string(), "Home".to_string()),
+        ("/home/a~1b".to_string(), "x".to_string()),
+        ("/list/0".to_string(), "one".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn round_trip_keeps_order_and_indent() {
+    let src = "{\n    \"z\": \"Last\",\n    \"a\": {\n        \"b\": \"B\",\n        \"n\": 1\n    }\n}\n";
+    let mut file = JsonFile::parse(src).unwrap();
+    assert_eq!(file.render().unwrap(), src);
+
+    file.apply(&HashMap::from([
+      ("/a/b".to_string(), "乙".to_string()),
+      ("/a/n".to_string(), "ignored".to_string()),
+      ("/missing".to_string(), "ignored".to_string()),
+    ]));
+    assert_eq!(
+      file.render().unwrap(),
+      "{\n    \"z\": \"Last\",\n    \"a\": {\n        \"b\": \"乙\",\n        \"n\": 1\n    }\n}\n"
+    );
+  }
+
+  #[test]
+  fn tab_indent_is_detected() {
+    let src = "{\n\t\"a\": \"A\"\n}\n";
+    assert_eq!(JsonFile::parse(src).unwrap().render().unwrap(), src);
+  }
+}
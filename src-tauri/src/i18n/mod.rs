@@ -0,0 +1,69 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+mod json;
+pub mod placeholder;
+mod po;
+mod properties;
+mod yaml;
+
+/// 支持的 i18n 资源文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum I18nFormat {
+  Json,
+  Yaml,
+  Properties,
+  Po,
+}
+
+impl I18nFormat {
+  /// 按扩展名识别格式
+  pub fn from_path(path: &Path) -> Option<Self> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+      "json" => Some(Self::Json),
+      "yaml" | "yml" => Some(Self::Yaml),
+      "properties" => Some(Self::Properties),
+      "po" | "pot" => Some(Self::Po),
+      _ => None,
+    }
+  }
+
+  pub fn parse(self, content: &str) -> Result<Box<dyn LocaleFile>, AppError> {
+    Ok(match self {
+      Self::Json => Box::new(json::JsonFile::parse(content)?),
+      Self::Yaml => Box::new(yaml::YamlFile::parse(content)?),
+      Self::Properties => Box::new(properties::PropertiesFile::parse(content)),
+      Self::Po => Box::new(po::PoFile::parse(content)?),
+    })
+  }
+}
+
+/// 解析后的资源文件：列出可翻译的字符串，写回译文后按原格式、原键顺序输出
+pub trait LocaleFile: Send {
+  /// 需要翻译的 (key, 原文)，按文件中出现的顺序
+  fn source_entries(&self) -> Vec<(String, String)>;
+
+  /// 作为目标文件读取时，已有的非空译文（缺失键模式用）
+  fn translated_entries(&self) -> HashMap<String, String> {
+    self
+      .source_entries()
+      .into_iter()
+      .filter(|(_, v)| !v.trim().is_empty())
+      .collect()
+  }
+
+  /// 按 key 写入译文（不认识的 key 忽略）
+  fn apply(&mut self, translations: &HashMap<String, String>);
+
+  fn render(&self) -> Result<String, AppError>;
+}
+
+/// JSON Pointer 风格的路径片段转义（"/" 和 "~" 在键名里也能用）
+fn escape_path_segment(segment: &str) -> String {
+  segment.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,463 @@
+// ======= 占位符保护 =======
+// 翻译前把占位符替换成 __PH0__ 这样的记号，翻译后再换回来，避免被翻译或打乱：
+// - {name} / {0} / ${name} / {{count}}
+// - ICU：{count, plural, one {# item} other {# items}}：只保护参数名、关键字、选择器和 #，
+//   各分支里的文字照常翻译；结构不完整时按花括号配对整体保护
+// - printf：%s / %d / %1$s / %.2f / %@ / %%
+// 术语表强制术语也复用这套记号：术语换成记号，译后换回目标术语
+
+/// 保护后的文本，以及按顺序记录的原始占位符
+#[derive(Debug)]
+pub struct Protected {
+  pub text: String,
+  /// 记号在译文里要换回的内容
+  placeholders: Vec<String>,
+  /// 记号在原文里替换掉的内容（占位符保护时和 placeholders 相同，术语保护时是源术语）
+  originals: Vec<String>,
+}
+
+impl Protected {
+  /// 去掉占位符后是否还有需要翻译的文字（只有占位符/标点的条目直接沿用原文）
+  pub fn is_translatable(&self) -> bool {
+    let mut rest = self.text.as_str();
+    while let Some((start, end, _)) = find_token(rest) {
+      if rest[..start].chars().any(char::is_alphabetic) {
+        return true;
+      }
+      rest = &rest[end..];
+    }
+    rest.chars().any(char::is_alphabetic)
+  }
+
+  /// 把译文里的记号换回原始占位符；有记号丢失、重复或无法识别时返回 None
+  pub fn restore(&self, translated: &str) -> Option<String> {
+    let mut seen = vec![false; self.placeholders.len()];
+    let out = replace_tokens(translated, |idx| {
+      let placeholder = self.placeholders.get(idx)?;
+      (!std::mem::replace(&mut seen[idx], true)).then_some(placeholder.as_str())
+    })?;
+
+    seen.iter().all(|s| *s).then_some(out)
+  }
+
+  /// 宽松版 restore：能识别的记号都换回去，不检查丢失和重复（用于逐段对照这类辅助信息）
+  pub fn restore_lenient(&self, translated: &str) -> String {
+    replace_tokens_lenient(translated, &self.placeholders)
+  }
+
+  /// 把保护后的文本（或其中一段）还原成原文
+  pub fn unprotect(&self, protected: &str) -> String {
+    replace_tokens_lenient(protected, &self.originals)
+  }
+}
+
+pub fn protect(text: &str) -> Protected {
+  let mut out = String::with_capacity(text.len());
+  let mut placeholders = Vec::new();
+  protect_into(text, false, &mut out, &mut placeholders);
+
+  Protected {
+    text: out,
+    originals: placeholders.clone(),
+    placeholders,
+  }
+}
+
+/// in_plural：是否在 plural / selectordinal 的分支里（分支里的 # 代表数字，也要保护）
+fn protect_into(text: &str, in_plural: bool, out: &mut String, placeholders: &mut Vec<String>) {
+  let mut i = 0;
+
+  while i < text.len() {
+    let rest = &text[i..];
+    if rest.starts_with('{') {
+      if let Some((pieces, len, plural)) = parse_icu(rest) {
+        for piece in pieces {
+          match piece {
+            IcuPiece::Syntax(s) => push_token(s, out, placeholders),
+            IcuPiece::Branch(s) => protect_into(s, plural, out, placeholders),
+          }
+        }
+        i += len;
+        continue;
+      }
+    }
+
+    let len = if rest.starts_with("${") {
+      brace_len(&rest[1..]).map(|n| n + 1)
+    } else if rest.starts_with('{') {
+      brace_len(rest)
+    } else if rest.starts_with('%') {
+      printf_len(rest)
+    } else if in_plural && rest.starts_with('#') {
+      Some(1)
+    } else {
+      None
+    };
+
+    match len {
+      Some(len) => {
+        push_token(&rest[..len], out, placeholders);
+        i += len;
+      }
+      None => {
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push(ch);
+        i += ch.len_utf8();
+      }
+    }
+  }
+}
+
+fn push_token(original: &str, out: &mut String, placeholders: &mut Vec<String>) {
+  out.push_str(&format!("__PH{}__", placeholders.len()));
+  placeholders.push(original.to_string());
+}
+
+enum IcuPiece<'a> {
+  /// 参数名、关键字、选择器和花括号
+  Syntax(&'a str),
+  /// 分支里的文字
+  Branch(&'a str),
+}
+
+/// 解析 "{count, plural, =0 {none} one {# item} other {# items}}"，
+/// 拆成交替的语法片段和分支文字，返回 (片段, 整段的字节长度, 是否 plural 类)；
+/// 不是完整的 ICU 复杂参数时返回 None
+fn parse_icu(s: &str) -> Option<(Vec<IcuPiece<'_>>, usize, bool)> {
+  let (mut i, keyword) = icu_head(s)?;
+  // select 的分支里 # 只是普通字符
+  let plural = keyword != "select";
+  let b = s.as_bytes();
+  let skip_ws = |mut i: usize| {
+    while b.get(i).is_some_and(u8::is_ascii_whitespace) {
+      i += 1;
+    }
+    i
+  };
+
+  let mut pieces = Vec::new();
+  // 当前这段语法从哪里开始（上一个分支的 '}' 或开头的 '{'）
+  let mut syntax_start = 0;
+  loop {
+    i = skip_ws(i);
+    if *b.get(i)? == b'}' {
+      if pieces.is_empty() {
+        return None;
+      }
+      pieces.push(IcuPiece::Syntax(&s[syntax_start..=i]));
+      return Some((pieces, i + 1, plural));
+    }
+
+    let selector_start = i;
+    while b.get(i).is_some_and(|c| !c.is_ascii_whitespace() && !matches!(c, b'{' | b'}')) {
+      i += 1;
+    }
+    if i == selector_start {
+      return None;
+    }
+    // plural 的 "offset:1" 不带分支
+    if s[selector_start..i].starts_with("offset:") {
+      continue;
+    }
+
+    i = skip_ws(i);
+    if b.get(i) != Some(&b'{') {
+      return None;
+    }
+    let len = brace_len(&s[i..])?;
+    pieces.push(IcuPiece::Syntax(&s[syntax_start..=i]));
+    pieces.push(IcuPiece::Branch(&s[i + 1..i + len - 1]));
+    syntax_start = i + len - 1;
+    i += len;
+  }
+}
+
+/// "{name, plural," -> (第二个逗号之后的位置, "plural")
+fn icu_head(s: &str) -> Option<(usize, &str)> {
+  let rest = s.strip_prefix('{')?;
+  let name_end = rest.find(',')?;
+  let name = rest[..name_end].trim();
+  if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+    return None;
+  }
+
+  let after_name = &rest[name_end + 1..];
+  let keyword_end = after_name.find(',')?;
+  let keyword = after_name[..keyword_end].trim();
+  if !matches!(keyword, "plural" | "select" | "selectordinal") {
+    return None;
+  }
+  Some((1 + name_end + 1 + keyword_end + 1, keyword))
+}
+
+/// 术语保护：terms 是 (源术语, 目标术语)，原文中的源术语换成记号，restore 时换成目标术语
+///
+/// 长的术语优先匹配；ASCII 术语不区分大小写，并且要求前后不是字母数字（避免 "cat" 命中 "category"）
+pub fn protect_terms(text: &str, terms: &[(String, String)]) -> Protected {
+  let mut sorted: Vec<&(String, String)> = terms.iter().filter(|(src, _)| !src.is_empty()).collect();
+  sorted.sort_by_key(|(src, _)| std::cmp::Reverse(src.len()));
+
+  let mut out = String::with_capacity(text.len());
+  let mut placeholders = Vec::new();
+  let mut originals = Vec::new();
+  let mut i = 0;
+
+  while i < text.len() {
+    let rest = &text[i..];
+    let matched = sorted.iter().find(|(src, _)| {
+      rest.get(..src.len()).is_some_and(|p| p.eq_ignore_ascii_case(src))
+        && !(starts_with_word_char(src) && text[..i].chars().next_back().is_some_and(is_word_char))
+        && !(ends_with_word_char(src) && rest[src.len()..].chars().next().is_some_and(is_word_char))
+    });
+
+    match matched {
+      Some((src, dst)) => {
+        out.push_str(&format!("__PH{}__", placeholders.len()));
+        placeholders.push(dst.clone());
+        originals.push(rest[..src.len()].to_string());
+        i += src.len();
+      }
+      None => {
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push(ch);
+        i += ch.len_utf8();
+      }
+    }
+  }
+
+  Protected {
+    text: out,
+    placeholders,
+    originals,
+  }
+}
+
+fn is_word_char(ch: char) -> bool {
+  ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+fn starts_with_word_char(s: &str) -> bool {
+  s.chars().next().is_some_and(is_word_char)
+}
+
+fn ends_with_word_char(s: &str) -> bool {
+  s.chars().next_back().is_some_and(is_word_char)
+}
+
+/// 逐个替换 s 里的记号；value 返回 None 时整体失败
+fn replace_tokens<'a>(s: &str, mut value: impl FnMut(usize) -> Option<&'a str>) -> Option<String> {
+  let mut out = String::with_capacity(s.len());
+  let mut rest = s;
+
+  while let Some((start, end, idx)) = find_token(rest) {
+    out.push_str(&rest[..start]);
+    out.push_str(value(idx)?);
+    rest = &rest[end..];
+  }
+  out.push_str(rest);
+  Some(out)
+}
+
+/// 序号越界的记号原样保留
+fn replace_tokens_lenient(s: &str, values: &[String]) -> String {
+  let mut out = String::with_capacity(s.len());
+  let mut rest = s;
+
+  while let Some((start, end, idx)) = find_token(rest) {
+    out.push_str(&rest[..start]);
+    out.push_str(values.get(idx).map_or(&rest[start..end], String::as_str));
+    rest = &rest[end..];
+  }
+  out.push_str(rest);
+  out
+}
+
+/// 从 '{' 开始找到配对的 '}'，返回整段的字节长度（不配对时返回 None）
+fn brace_len(s: &str) -> Option<usize> {
+  let mut depth = 0usize;
+  for (i, ch) in s.char_indices() {
+    match ch {
+      '{' => depth += 1,
+      '}' => {
+        depth -= 1;
+        if depth == 0 {
+          return Some(i + 1);
+        }
+      }
+      _ => {}
+    }
+  }
+  None
+}
+
+/// printf 风格：%[n$][flags][width][.precision][length]conversion
+/// flags 不包含空格，避免把 "50% off" 误判成 "% o"
+fn printf_len(s: &str) -> Option<usize> {
+  let b = s.as_bytes();
+  if b.get(1) == Some(&b'%') {
+    return Some(2);
+  }
+
+  let digits = |mut i: usize| {
+    while b.get(i).is_some_and(u8::is_ascii_digit) {
+      i += 1;
+    }
+    i
+  };
+
+  let mut i = 1;
+  // 位置参数 %1$s
+  let j = digits(i);
+  if j > i && b.get(j) == Some(&b'$') {
+    i = j + 1;
+  }
+  while b.get(i).is_some_and(|c| b"-+0#'".contains(c)) {
+    i += 1;
+  }
+  i = if b.get(i) == Some(&b'*') { i + 1 } else { digits(i) };
+  if b.get(i) == Some(&b'.') {
+    i = digits(i + 1);
+  }
+  while b.get(i).is_some_and(|c| b"hlLqjzt".contains(c)) {
+    i += 1;
+  }
+
+  b.get(i)
+    .filter(|c| b"sdiuoxXfFeEgGaAcCpn@".contains(c))
+    .map(|_| i + 1)
+}
+
+/// 在 s 中找第一个 __PH{n}__ 记号，返回 (起始, 结束, n)
+/// 翻译服务偶尔会改大小写或在记号里插空格，这里都容忍
+fn find_token(s: &str) -> Option<(usize, usize, usize)> {
+  let mut from = 0;
+  while let Some(pos) = s[from..].find("__") {
+    let start = from + pos;
+    let rest = s[start + 2..].trim_start();
+
+    if rest.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("ph")) {
+      let after_ph = rest[2..].trim_start();
+      let digits = after_ph.bytes().take_while(u8::is_ascii_digit).count();
+      let tail = after_ph[digits..].trim_start();
+
+      if digits > 0 && tail.starts_with("__") {
+        if let Ok(idx) = after_ph[..digits].parse() {
+          let end = s.len() - tail.len() + 2;
+          return Some((start, end, idx));
+        }
+      }
+    }
+
+    // "___PH0__" 这种多一个下划线的情况：只前进一个字节再找
+    from = start + 1;
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn simple_placeholders_round_trip() {
+    let text = "Hi {name}, ${user} has {{count}} items ({0}) at 50% off: %s %1$d %.2f %@ %%";
+    let p = protect(text);
+    assert_eq!(p.placeholders, ["{name}", "${user}", "{{count}}", "{0}", "%s", "%1$d", "%.2f", "%@", "%%"]);
+    assert!(!p.text.contains('{') && !p.text.contains("%s"));
+    assert!(p.text.contains("50% off"));
+    assert_eq!(p.restore(&p.text).unwrap(), text);
+  }
+
+  #[test]
+  fn icu_plural_keeps_branch_text_translatable() {
+    let text = "{count, plural, =0 {No items} one {# item} other {# items}}";
+    let p = protect(text);
+    assert_eq!(p.text, "__PH0__No items__PH1____PH2__ item__PH3____PH4__ items__PH5__");
+    assert_eq!(
+      p.placeholders,
+      ["{count, plural, =0 {", "} one {", "#", "} other {", "#", "}}"]
+    );
+    assert!(p.is_translatable());
+
+    let translated = "__PH0__没有项目__PH1____PH2__ 个项目__PH3____PH4__ 个项目__PH5__";
+    assert_eq!(
+      p.restore(translated).unwrap(),
+      "{count, plural, =0 {没有项目} one {# 个项目} other {# 个项目}}"
+    );
+  }
+
+  #[test]
+  fn icu_select_and_nested_placeholders() {
+    let p = protect("{gender, select, male {He} other {They}} invited {name}");
+    assert_eq!(p.placeholders, ["{gender, select, male {", "} other {", "}}", "{name}"]);
+    assert_eq!(p.text, "__PH0__He__PH1__They__PH2__ invited __PH3__");
+
+    // select 分支里的 # 不是数字占位
+    let p = protect("{kind, select, tag {#hashtag} other {x}}");
+    assert!(p.text.contains("#hashtag"));
+
+    let p = protect("{n, plural, offset:1 one {{name} and # other} other {{name} and # others}}");
+    assert_eq!(p.placeholders[0], "{n, plural, offset:1 one {");
+    assert!(p.placeholders.contains(&"{name}".to_string()));
+    assert!(p.text.contains(" and "));
+    assert_eq!(p.restore(&p.text).unwrap(), p.unprotect(&p.text));
+  }
+
+  #[test]
+  fn malformed_icu_is_protected_whole() {
+    let p = protect("{count, plural, one} left");
+    assert_eq!(p.placeholders, ["{count, plural, one}"]);
+    // 外层花括号不配对：只有内层的 {# item} 被当成普通占位符
+    let p = protect("{count, plural, one {# item}");
+    assert_eq!(p.text, "{count, plural, one __PH0__");
+  }
+
+  #[test]
+  fn restore_rejects_missing_or_duplicate_tokens() {
+    let p = protect("{a} and {b}");
+    assert_eq!(p.restore("__ph1__ 和 __ PH0 __").unwrap(), "{b} 和 {a}");
+    assert!(p.restore("__PH0__ 和").is_none());
+    assert!(p.restore("__PH0__ __PH0__ __PH1__").is_none());
+    assert!(p.restore("__PH0__ __PH1__ __PH2__").is_none());
+  }
+
+  fn terms(items: &[(&str, &str)]) -> Vec<(String, String)> {
+    items.iter().map(|(s, t)| (s.to_string(), t.to_string())).collect()
+  }
+
+  #[test]
+  fn protect_terms_respects_word_boundaries() {
+    let p = protect_terms("cat, Cat and category; my_cat; cat.", &terms(&[("cat", "猫")]));
+    assert_eq!(p.text, "__PH0__, __PH1__ and category; my_cat; __PH2__.");
+    assert_eq!(p.unprotect(&p.text), "cat, Cat and category; my_cat; cat.");
+    assert_eq!(p.restore(&p.text).unwrap(), "猫, 猫 and category; my_cat; 猫.");
+
+    // 非 ASCII 术语没有词边界的概念
+    let p = protect_terms("使用机器学习", &terms(&[("机器", "machine")]));
+    assert_eq!(p.text, "使用__PH0__学习");
+  }
+
+  #[test]
+  fn protect_terms_prefers_longest_match() {
+    let p = protect_terms(
+      "machine learning and machine",
+      &terms(&[("machine", "机器"), ("machine learning", "机器学习"), ("", "ignored")]),
+    );
+    assert_eq!(p.text, "__PH0__ and __PH1__");
+    assert_eq!(p.restore("__PH0__和__PH1__").unwrap(), "机器学习和机器");
+  }
+
+  #[test]
+  fn restore_lenient_keeps_unknown_tokens() {
+    let p = protect_terms("Rust and Go", &terms(&[("Rust", "锈"), ("Go", "围棋")]));
+    assert_eq!(p.restore_lenient("__PH1__ 和 __PH1__"), "围棋 和 围棋");
+    assert_eq!(p.restore_lenient("__PH0__ __PH9__"), "锈 __PH9__");
+    assert!(p.restore("__PH1__ 和 __PH1__").is_none());
+  }
+
+  #[test]
+  fn placeholder_only_text_is_not_translatable() {
+    assert!(!protect("{0} / %s").is_translatable());
+    assert!(protect("{0} files").is_translatable());
+  }
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use super::LocaleFile;
+use crate::error::AppError;
+
+/// gettext 约定：msgctxt 和 msgid 之间用 \x04 分隔
+const CONTEXT_SEPARATOR: char = '\u{4}';
+/// 复数形式的 key 后缀（msgid_plural 对应 msgstr[1..]）
+const PLURAL_SUFFIX: &str = "\u{0}plural";
+
+/// gettext .po / .pot：注释、引用位置、flags 原样保留，只重写 msgstr
+pub struct PoFile {
+  lines: Vec<String>,
+  entries: Vec<PoEntry>,
+}
+
+struct PoEntry {
+  key: String,
+  msgid: String,
+  msgid_plural: Option<String>,
+  /// msgstr 或 msgstr[0..n]
+  msgstr: Vec<String>,
+  /// msgstr 在 lines 中占的行（左闭右开），写回时整体替换
+  msgstr_lines: (usize, usize),
+  changed: bool,
+}
+
+/// 当前正在读的关键字（后面的 "..." 续行拼到它上面）
+enum Field {
+  Ctxt,
+  Id,
+  IdPlural,
+  Str,
+}
+
+impl PoFile {
+  pub fn parse(content: &str) -> Result<Self, AppError> {
+    let lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut entries = Vec::new();
+    let mut builder = EntryBuilder::default();
+
+    for (i, raw) in lines.iter().enumerate() {
+      let line = raw.trim();
+
+      // 空行 / 注释（包括 #~ 废弃条目）结束当前条目
+      if line.is_empty() || line.starts_with('#') {
+        if let Some(entry) = builder.finish(i) {
+          entries.push(entry);
+        }
+        continue;
+      }
+
+      let bad_line = || AppError::msg(format!("无法解析 .po 文件第 {} 行：{raw}", i + 1));
+
+      if line.starts_with('"') {
+        let text = unquote(line).ok_or_else(bad_line)?;
+        builder.append(&text).ok_or_else(bad_line)?;
+        continue;
+      }
+
+      let (keyword, rest) = line.split_once(char::is_whitespace).ok_or_else(bad_line)?;
+      let text = unquote(rest.trim()).ok_or_else(bad_line)?;
+
+      // 新条目从 msgctxt 或 msgid 开始（条目之间没有空行时靠这里切分）
+      if matches!(keyword, "msgctxt" | "msgid") && builder.has_msgstr() {
+        if let Some(entry) = builder.finish(i) {
+          entries.push(entry);
+        }
+      }
+
+      match keyword {
+        "msgctxt" => builder.set(Field::Ctxt, text),
+        "msgid" => builder.set(Field::Id, text),
+        "msgid_plural" => builder.set(Field::IdPlural, text),
+        k if k == "msgstr" || k.starts_with("msgstr[") => builder.push_msgstr(text, i),
+        _ => return Err(bad_line()),
+      }
+    }
+    if let Some(entry) = builder.finish(lines.len()) {
+      entries.push(entry);
+    }
+
+    Ok(Self { lines, entries })
+  }
+}
+
+impl LocaleFile for PoFile {
+  fn source_entries(&self) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for entry in self.entries.iter().filter(|e| !e.is_header()) {
+      out.push((entry.key.clone(), entry.msgid.clone()));
+      if let Some(plural) = &entry.msgid_plural {
+        out.push((format!("{}{PLURAL_SUFFIX}", entry.key), plural.clone()));
+      }
+    }
+    out
+  }
+
+  fn translated_entries(&self) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+    for entry in self.entries.iter().filter(|e| !e.is_header()) {
+      if let Some(s) = entry.msgstr.first().filter(|s| !s.is_empty()) {
+        out.insert(entry.key.clone(), s.clone());
+      }
+      if let Some(s) = entry.msgstr.get(1).filter(|s| !s.is_empty()) {
+        out.insert(format!("{}{PLURAL_SUFFIX}", entry.key), s.clone());
+      }
+    }
+    out
+  }
+
+  fn apply(&mut self, translations: &HashMap<String, String>) {
+    for entry in self.entries.iter_mut().filter(|e| !e.is_header()) {
+      if let Some(text) = translations.get(&entry.key) {
+        if entry.msgstr.is_empty() {
+          entry.msgstr.push(String::new());
+        }
+        entry.msgstr[0] = text.clone();
+        entry.changed = true;
+      }
+
+      if entry.msgid_plural.is_some() {
+        let plural_key = format!("{}{PLURAL_SUFFIX}", entry.key);
+        if let Some(text) = translations.get(&plural_key) {
+          // 复数形式数量由目标语言决定，这里沿用已有数量（至少两个），其余形式都用复数译文
+          let forms = entry.msgstr.len().max(2);
+          entry.msgstr.resize(forms, String::new());
+          for s in entry.msgstr.iter_mut().skip(1) {
+            *s = text.clone();
+          }
+          entry.changed = true;
+        }
+      }
+    }
+  }
+
+  fn render(&self) -> Result<String, AppError> {
+    let mut out = String::new();
+    let mut changed = self.entries.iter().filter(|e| e.changed).peekable();
+    let mut i = 0;
+
+    while i < self.lines.len() {
+      if let Some(entry) = changed.next_if(|e| e.msgstr_lines.0 == i) {
+        write_msgstr(&mut out, entry);
+        i = entry.msgstr_lines.1.max(i + 1);
+        continue;
+      }
+      out.push_str(&self.lines[i]);
+      out.push('\n');
+      i += 1;
+    }
+    Ok(out)
+  }
+}
+
+impl PoEntry {
+  /// msgid "" 的条目是文件头（Content-Type、Plural-Forms 等）
+  fn is_header(&self) -> bool {
+    self.key.is_empty()
+  }
+}
+
+#[derive(Default)]
+struct EntryBuilder {
+  msgctxt: Option<String>,
+  msgid: Option<String>,
+  msgid_plural: Option<String>,
+  msgstr: Vec<String>,
+  msgstr_start: Option<usize>,
+  field: Option<Field>,
+}
+
+impl EntryBuilder {
+  fn set(&mut self, field: Field, text: String) {
+    match field {
+      Field::Ctxt => self.msgctxt = Some(text),
+      Field::Id => self.msgid = Some(text),
+      Field::IdPlural => self.msgid_plural = Some(text),
+      Field::Str => self.msgstr.push(text),
+    }
+    self.field = Some(field);
+  }
+
+  fn push_msgstr(&mut self, text: String, line: usize) {
+    self.msgstr_start.get_or_insert(line);
+    self.set(Field::Str, text);
+  }
+
+  fn has_msgstr(&self) -> bool {
+    self.msgstr_start.is_some()
+  }
+
+  /// 续行："..." 拼到当前关键字上
+  fn append(&mut self, text: &str) -> Option<()> {
+    let target = match self.field.as_ref()? {
+      Field::Ctxt => self.msgctxt.as_mut()?,
+      Field::Id => self.msgid.as_mut()?,
+      Field::IdPlural => self.msgid_plural.as_mut()?,
+      Field::Str => self.msgstr.last_mut()?,
+    };
+    target.push_str(text);
+    Some(())
+  }
+
+  /// 结束当前条目；end 是条目之后的第一行（msgstr 续行到这里为止）
+  fn finish(&mut self, end: usize) -> Option<PoEntry> {
+    let builder = std::mem::take(self);
+    let msgid = builder.msgid?;
+    let start = builder.msgstr_start?;
+
+    let key = match builder.msgctxt {
+      Some(ctx) => format!("{ctx}{CONTEXT_SEPARATOR}{msgid}"),
+      None => msgid.clone(),
+    };
+
+    Some(PoEntry {
+      key,
+      msgid,
+      msgid_plural: builder.msgid_plural,
+      msgstr: builder.msgstr,
+      msgstr_lines: (start, end),
+      changed: false,
+    })
+  }
+}
+
+fn write_msgstr(out: &mut String, entry: &PoEntry) {
+  let plural = entry.msgid_plural.is_some();
+  for (i, text) in entry.msgstr.iter().enumerate() {
+    let keyword = if plural { format!("msgstr[{i}]") } else { "msgstr".to_string() };
+
+    // 多行文本按 gettext 习惯拆成 msgstr "" + 每行一个字符串
+    let pieces: Vec<&str> = text.split_inclusive('\n').collect();
+    if pieces.len() > 1 {
+      out.push_str(&format!("{keyword} \"\"\n"));
+      for piece in pieces {
+        out.push_str(&format!("\"{}\"\n", escape(piece)));
+      }
+    } else {
+      out.push_str(&format!("{keyword} \"{}\"\n", escape(text)));
+    }
+  }
+}
+
+/// "..." -> 反转义后的内容
+fn unquote(s: &str) -> Option<String> {
+  let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+  let mut out = String::with_capacity(inner.len());
+  let mut chars = inner.chars();
+
+  while let Some(ch) = chars.next() {
+    if ch != '\\' {
+      out.push(ch);
+      continue;
+    }
+    match chars.next()? {
+      'n' => out.push('\n'),
+      't' => out.push('\t'),
+      'r' => out.push('\r'),
+      other => out.push(other),
+    }
+  }
+  Some(out)
+}
+
+fn escape(s: &str) -> String {
+  s.replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+    .replace('\t', "\\t")
+    .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SAMPLE: &str = r#"# Translator comment
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+"Plural-Forms: nplurals=2; plural=(n != 1);\n"
+
+#: src/app.c:10
+#, c-format
+msgid "Hello %s"
+msgstr ""
+
+msgctxt "menu"
+msgid "Open"
+msgstr "Öffnen"
+
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] ""
+msgstr[1] ""
+
+msgid ""
+"multi "
+"line"
+msgstr ""
+"#;
+
+  #[test]
+  fn entries_skip_header_and_keep_context_and_plural() {
+    let file = PoFile::parse(SAMPLE).unwrap();
+    assert_eq!(
+      file.source_entries(),
+      [
+        ("Hello %s".to_string(), "Hello %s".to_string()),
+        ("menu\u{4}Open".to_string(), "Open".to_string()),
+        ("One file".to_string(), "One file".to_string()),
+        (format!("One file{PLURAL_SUFFIX}"), "%d files".to_string()),
+        ("multi line".to_string(), "multi line".to_string()),
+      ]
+    );
+    assert_eq!(
+      file.translated_entries(),
+      HashMap::from([("menu\u{4}Open".to_string(), "Öffnen".to_string())])
+    );
+  }
+
+  #[test]
+  fn round_trip_unchanged() {
+    let file = PoFile::parse(SAMPLE).unwrap();
+    assert_eq!(file.render().unwrap(), SAMPLE);
+  }
+
+  #[test]
+  fn apply_rewrites_only_msgstr() {
+    let mut file = PoFile::parse(SAMPLE).unwrap();
+    file.apply(&HashMap::from([
+      ("Hello %s".to_string(), "你好 \"%s\"".to_string()),
+      ("One file".to_string(), "一个文件".to_string()),
+      (format!("One file{PLURAL_SUFFIX}"), "%d 个文件".to_string()),
+      ("multi line".to_string(), "第一行\n第二行".to_string()),
+    ]));
+    let out = file.render().unwrap();
+
+    assert!(out.contains("#: src/app.c:10\n#, c-format\nmsgid \"Hello %s\"\nmsgstr \"你好 \\\"%s\\\"\"\n"));
+    assert!(out.contains("msgstr[0] \"一个文件\"\nmsgstr[1] \"%d 个文件\"\n"));
+    assert!(out.contains("msgstr \"\"\n\"第一行\\n\"\n\"第二行\"\n"));
+    assert!(out.contains("msgstr \"Öffnen\"\n"));
+    assert!(out.starts_with("# Translator comment\nmsgid \"\"\nmsgstr \"\"\n\"Content-Type"));
+
+    let reparsed = PoFile::parse(&out).unwrap().translated_entries();
+    assert_eq!(reparsed["Hello %s"], "你好 \"%s\"");
+    assert_eq!(reparsed["multi line"], "第一行\n第二行");
+    assert_eq!(reparsed[&format!("One file{PLURAL_SUFFIX}")], "%d 个文件");
+  }
+
+  #[test]
+  fn invalid_line_is_error() {
+    assert!(PoFile::parse("msgid \"a\"\nmsgstr unquoted\n").is_err());
+    assert!(PoFile::parse("bogus \"a\"\n").is_err());
+  }
+}
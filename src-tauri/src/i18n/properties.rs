@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use super::LocaleFile;
+use crate::error::AppError;
+
+/// Java .properties：注释、空行、键的写法都原样保留，只替换值
+pub struct PropertiesFile {
+  lines: Vec<Line>,
+  newline: &'static str,
+  /// 源文件全是 ASCII（用 \uXXXX 写非 ASCII 字符）时，输出也保持这种写法
+  ascii_only: bool,
+}
+
+enum Line {
+  Raw(String),
+  Entry {
+    /// 值之前的原始内容（缩进 + 键 + 分隔符）
+    prefix: String,
+    key: String,
+    value: String,
+  },
+}
+
+impl PropertiesFile {
+  pub fn parse(content: &str) -> Self {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines = Vec::new();
+    let mut physical = content.lines();
+
+    while let Some(first) = physical.next() {
+      let trimmed = first.trim_start();
+      if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+        lines.push(Line::Raw(first.to_string()));
+        continue;
+      }
+
+      // 行尾奇数个反斜杠表示续行：去掉反斜杠，下一行去掉前导空白后拼上
+      let mut logical = first.to_string();
+      while ends_with_continuation(&logical) {
+        logical.pop();
+        match physical.next() {
+          Some(next) => logical.push_str(next.trim_start()),
+          None => break,
+        }
+      }
+
+      lines.push(parse_entry(&logical));
+    }
+
+    Self {
+      lines,
+      newline,
+      ascii_only: content.is_ascii(),
+    }
+  }
+}
+
+impl LocaleFile for PropertiesFile {
+  fn source_entries(&self) -> Vec<(String, String)> {
+    self
+      .lines
+      .iter()
+      .filter_map(|l| match l {
+        Line::Entry { key, value, .. } => Some((key.clone(), value.clone())),
+        Line::Raw(_) => None,
+      })
+      .collect()
+  }
+
+  fn apply(&mut self, translations: &HashMap<String, String>) {
+    for line in &mut self.lines {
+      if let Line::Entry { key, value, .. } = line {
+        if let Some(text) = translations.get(key.as_str()) {
+          *value = text.clone();
+        }
+      }
+    }
+  }
+
+  fn render(&self) -> Result<String, AppError> {
+    let mut out = String::new();
+    for line in &self.lines {
+      match line {
+        Line::Raw(raw) => out.push_str(raw),
+        Line::Entry { prefix, value, .. } => {
+          out.push_str(prefix);
+          out.push_str(&escape_value(value, self.ascii_only));
+        }
+      }
+      out.push_str(self.newline);
+    }
+    Ok(out)
+  }
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+  line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// key 到第一个未转义的 '=' / ':' / 空白为止；分隔符两侧的空白都算作分隔符
+fn parse_entry(line: &str) -> Line {
+  let indent = line.len() - line.trim_start().len();
+  let mut key_end = line.len();
+  let mut escaped = false;
+
+  for (i, ch) in line.char_indices().skip_while(|(i, _)| *i < indent) {
+    if escaped {
+      escaped = false;
+      continue;
+    }
+    match ch {
+      '\\' => escaped = true,
+      '=' | ':' | ' ' | '\t' | '\x0c' => {
+        key_end = i;
+        break;
+      }
+      _ => {}
+    }
+  }
+
+  let rest = &line[key_end..];
+  let mut value_start = key_end + (rest.len() - rest.trim_start().len());
+  if line[value_start..].starts_with(['=', ':']) {
+    value_start += 1;
+    let after = &line[value_start..];
+    value_start += after.len() - after.trim_start().len();
+  }
+
+  Line::Entry {
+    prefix: line[..value_start].to_string(),
+    key: unescape(&line[indent..key_end]),
+    value: unescape(&line[value_start..]),
+  }
+}
+
+/// \uXXXX 是 UTF-16 码元：BMP 以外的字符（例如 emoji）写成 \uD83D\uDE00 这样的代理对，
+/// 连续的 \u 先攒起来再一起解码
+fn unescape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  let mut units: Vec<u16> = Vec::new();
+  let mut chars = s.chars().peekable();
+
+  while let Some(ch) = chars.next() {
+    if ch == '\\' && chars.peek() == Some(&'u') {
+      chars.next();
+      let hex: String = chars.by_ref().take(4).collect();
+      match u16::from_str_radix(&hex, 16) {
+        Ok(unit) if hex.len() == 4 => units.push(unit),
+        _ => {
+          flush_utf16(&mut units, &mut out);
+          out.push_str(&hex);
+        }
+      }
+      continue;
+    }
+
+    flush_utf16(&mut units, &mut out);
+    if ch != '\\' {
+      out.push(ch);
+      continue;
+    }
+    match chars.next() {
+      Some('t') => out.push('\t'),
+      Some('n') => out.push('\n'),
+      Some('r') => out.push('\r'),
+      Some('f') => out.push('\x0c'),
+      Some(other) => out.push(other),
+      None => {}
+    }
+  }
+  flush_utf16(&mut units, &mut out);
+  out
+}
+
+/// 落单的代理项解不出来，换成 U+FFFD
+fn flush_utf16(units: &mut Vec<u16>, out: &mut String) {
+  out.extend(char::decode_utf16(units.drain(..)).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)));
+}
+
+fn escape_value(value: &str, ascii_only: bool) -> String {
+  let mut out = String::with_capacity(value.len());
+  for (i, ch) in value.chars().enumerate() {
+    match ch {
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      // 值开头的空格会被当成分隔符吃掉，需要转义
+      ' ' if i == 0 => out.push_str("\\ "),
+      c if ascii_only && !c.is_ascii() => {
+        let mut buf = [0u16; 2];
+        for unit in c.encode_utf16(&mut buf) {
+          out.push_str(&format!("\\u{unit:04X}"));
+        }
+      }
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entries(file: &PropertiesFile) -> Vec<(String, String)> {
+    file.source_entries()
+  }
+
+  #[test]
+  fn parse_keys_separators_and_continuations() {
+    let file = PropertiesFile::parse(
+      "# comment\n! also comment\n\na=1\nb : 2\nc 3\nd\\ key=4\nlong = first \\\n    second\n",
+    );
+    assert_eq!(
+      entries(&file),
+      [
+        ("a".to_string(), "1".to_string()),
+        ("b".to_string(), "2".to_string()),
+        ("c".to_string(), "3".to_string()),
+        ("d key".to_string(), "4".to_string()),
+        ("long".to_string(), "first second".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn unescape_surrogate_pairs() {
+    assert_eq!(unescape("\\uD83D\\uDE00"), "😀");
+    assert_eq!(unescape("a\\u4F60\\u597D\\uD83D\\uDE00b"), "a你好😀b");
+    assert_eq!(unescape("\\uD83D!"), "\u{FFFD}!");
+    assert_eq!(unescape("tab\\there\\nnext\\\\"), "tab\there\nnext\\");
+  }
+
+  #[test]
+  fn round_trip_keeps_layout() {
+    let src = "# title\r\ngreeting = Hello\r\n\r\nfarewell: Bye\r\n";
+    let mut file = PropertiesFile::parse(src);
+    assert_eq!(file.render().unwrap(), src);
+
+    let translations = HashMap::from([("greeting".to_string(), "你好".to_string())]);
+    file.apply(&translations);
+    assert_eq!(file.render().unwrap(), "# title\r\ngreeting = \\u4F60\\u597D\r\n\r\nfarewell: Bye\r\n");
+  }
+
+  #[test]
+  fn round_trip_non_ascii_source_and_emoji() {
+    let mut file = PropertiesFile::parse("smile=\\uD83D\\uDE00\nname=名字\n");
+    assert_eq!(entries(&file)[0].1, "😀");
+
+    let translations = HashMap::from([
+      ("smile".to_string(), " 😀\n".to_string()),
+      ("name".to_string(), "Name".to_string()),
+    ]);
+    file.apply(&translations);
+    let out = file.render().unwrap();
+    assert_eq!(out, "smile=\\ 😀\\n\nname=Name\n");
+    assert_eq!(entries(&PropertiesFile::parse(&out))[0].1, " 😀\n");
+
+    // 纯 ASCII 源文件写回时 emoji 编成代理对，重新解析后不变
+    let mut ascii = PropertiesFile::parse("smile=x\n");
+    ascii.apply(&HashMap::from([("smile".to_string(), "😀".to_string())]));
+    let out = ascii.render().unwrap();
+    assert_eq!(out, "smile=\\uD83D\\uDE00\n");
+    assert_eq!(entries(&PropertiesFile::parse(&out))[0].1, "😀");
+  }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use serde_yaml::Value;
+
+use super::{escape_path_segment, LocaleFile};
+use crate::error::AppError;
+
+/// YAML 资源文件：键顺序保持不变；注释和引号风格无法保留（serde_yaml 不支持）
+pub struct YamlFile {
+  root: Value,
+}
+
+impl YamlFile {
+  pub fn parse(content: &str) -> Result<Self, AppError> {
+    let root = serde_yaml::from_str(content).map_err(|e| AppError::Serde(format!("parse yaml failed: {e}")))?;
+    Ok(Self { root })
+  }
+}
+
+impl LocaleFile for YamlFile {
+  fn source_entries(&self) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    collect(&self.root, String::new(), &mut out);
+    out
+  }
+
+  fn apply(&mut self, translations: &HashMap<String, String>) {
+    apply(&mut self.root, String::new(), translations);
+  }
+
+  fn render(&self) -> Result<String, AppError> {
+    serde_yaml::to_string(&self.root).map_err(|e| AppError::Serde(format!("write yaml failed: {e}")))
+  }
+}
+
+fn collect(value: &Value, path: String, out: &mut Vec<(String, String)>) {
+  match value {
+    Value::String(s) => out.push((path, s.clone())),
+    Value::Sequence(items) => {
+      for (i, item) in items.iter().enumerate() {
+        collect(item, format!("{path}/{i}"), out);
+      }
+    }
+    Value::Mapping(map) => {
+      for (k, v) in map {
+        if let Some(segment) = key_segment(k) {
+          collect(v, format!("{path}/{segment}"), out);
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+fn apply(value: &mut Value, path: String, translations: &HashMap<String, String>) {
+  match value {
+    Value::String(s) => {
+      if let Some(text) = translations.get(&path) {
+        *s = text.clone();
+      }
+    }
+    Value::Sequence(items) => {
+      for (i, item) in items.iter_mut().enumerate() {
+        apply(item, format!("{path}/{i}"), translations);
+      }
+    }
+    Value::Mapping(map) => {
+      for (k, v) in map.iter_mut() {
+        if let Some(segment) = key_segment(k) {
+          apply(v, format!("{path}/{segment}"), translations);
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+/// YAML 的键不一定是字符串（true / 1 也是合法键）
+fn key_segment(key: &Value) -> Option<String> {
+  let raw = match key {
+    Value::String(s) => s.clone(),
+    Value::Number(n) => n.to_string(),
+    Value::Bool(b) => b.to_string(),
+    _ => return None,
+  };
+  Some(escape_path_segment(&raw))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn entries_cover_nested_maps_sequences_and_non_string_keys() {
+    let file = YamlFile::parse("home:\n  title: Home\n  a/b: x\nlist:\n  - one\n  - 2\n1: numeric key\n").unwrap();
+    assert_eq!(
+      file.source_entries(),
+      [
+        ("/home/title".to_string(), "Home".to_string()),
+        ("/home/a~1b".to_string(), "x".to_string()),
+        ("/list/0".to_string(), "one".to_string()),
+        ("/1".to_string(), "numeric key".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn round_trip_keeps_key_order() {
+    let mut file = YamlFile::parse("z: Last\na:\n  b: B\n  n: 1\n").unwrap();
+    file.apply(&HashMap::from([
+      ("/a/b".to_string(), "乙".to_string()),
+      ("/a/n".to_string(), "ignored".to_string()),
+    ]));
+    let out = file.render().unwrap();
+    assert_eq!(out, "z: Last\na:\n  b: 乙\n  n: 1\n");
+
+    let reparsed = YamlFile::parse(&out).unwrap();
+    assert_eq!(reparsed.source_entries()[1], ("/a/b".to_string(), "乙".to_string()));
+  }
+
+  #[test]
+  fn invalid_yaml_is_error() {
+    assert!(YamlFile::parse("a: [unclosed").is_err());
+  }
+}
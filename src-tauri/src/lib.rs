@@ -1,13 +1,23 @@
 use tauri::{Emitter, Manager, PhysicalPosition, PhysicalSize};
 use tauri::menu::{MenuBuilder, MenuItem, SubmenuBuilder};
 
+mod cache;
 mod commands;
+mod crypto;
 mod db;
+mod doc_job;
 mod error;
+mod glossary;
+mod history;
+mod http;
+mod i18n;
 mod settings;
+mod translate;
 
 use db::init_db;
 use crate::commands::baidu_translate::BaiduTokenState;
+use crate::doc_job::DocJobState;
+use crate::http::HttpClientState;
 
 const ABOUT_WINDOW_LABEL: &str = "about_window";
 const MAIN_WINDOW_LABEL: &str = "main";
@@ -52,6 +62,8 @@ fn center_on_primary_monitor(
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
+    .plugin(tauri_plugin_clipboard_manager::init())
+    .plugin(tauri_plugin_dialog::init())
     .setup(|app| {
       // --- items ---
       let about = MenuItem::with_id(app, "about", "关于", true, None::<&str>)?;
@@ -132,8 +144,13 @@ pub fn run() {
       });
 
       let pool = init_db(app.handle())?;
+      let baidu_token = BaiduTokenState::load(&pool);
       app.manage(pool);
-      app.manage(BaiduTokenState::default());
+      app.manage(HttpClientState::default());
+      app.manage(baidu_token);
+      app.manage(DocJobState::default());
+      // 上次退出时还没完成的文档翻译任务继续轮询
+      doc_job::resume(app.handle());
 
       if cfg!(debug_assertions) {
         app.handle().plugin(
@@ -150,10 +167,44 @@ pub fn run() {
       commands::settings::settings_save_api_keys,
       commands::settings::settings_get_network,
       commands::settings::settings_save_network,
+      commands::settings::settings_test_network,
+      commands::settings::settings_resolve_system_proxy,
+      commands::settings::settings_get_network_overrides,
+      commands::settings::settings_save_network_overrides,
+      commands::settings::settings_get_doc_output_dir,
+      commands::settings::settings_save_doc_output_dir,
       commands::baidu_translate::baidu_text_translate,
+      commands::baidu_translate::baidu_text_translate_batch,
       commands::baidu_translate::baidu_pic_translate,
+      commands::clipboard::baidu_pic_translate_clipboard,
       commands::baidu_translate::baidu_doc_translate_create,
       commands::baidu_translate::baidu_doc_translate_query,
+      commands::youdao_translate::youdao_text_translate,
+      commands::deepl_translate::deepl_translate,
+      commands::deepl_translate::deepl_usage,
+      commands::translate::translate_text,
+      commands::translate::translate_compare,
+      commands::translate::translate_detect_language,
+      commands::translate::translate_supported_languages,
+      commands::cache::translate_cache_stats,
+      commands::cache::translate_cache_purge,
+      commands::i18n::i18n_translate_file,
+      commands::doc_job::doc_translate_job_list,
+      commands::doc_job::doc_translate_job_delete,
+      commands::doc_job::doc_translate_job_retry,
+      commands::history::history_list,
+      commands::history::history_search,
+      commands::history::history_set_favorite,
+      commands::history::history_delete,
+      commands::history::history_clear,
+      commands::glossary::glossary_list,
+      commands::glossary::glossary_create,
+      commands::glossary::glossary_update,
+      commands::glossary::glossary_delete,
+      commands::glossary::glossary_terms,
+      commands::glossary::glossary_import,
+      commands::glossary::glossary_upsert_term,
+      commands::glossary::glossary_delete_term,
       commands::github::github_repo_commit_activity,
     ])
     .run(tauri::generate_context!())
@@ -4,38 +4,28 @@ pub use types::*;
 
 use chrono::Utc;
 use rusqlite::params;
+use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{db::DbPool, error::AppError};
+use crate::{crypto, db::DbPool, error::AppError};
 
 const KEY_API_KEYS: &str = "api_keys";
 const KEY_NETWORK_PROXY: &str = "network_proxy";
+const KEY_NETWORK_OVERRIDES: &str = "network_proxy_overrides";
+const KEY_DOC_OUTPUT_DIR: &str = "doc_output_dir";
 
 /* ==================== API KEYS ==================== */
 
 pub fn save_api_keys(pool: &DbPool, payload: &ApiKeysForm) -> Result<(), AppError> {
-  let json =
-    serde_json::to_string(payload).map_err(|e| AppError::Serde(format!("to json failed: {e}")))?;
-
-  let now = Utc::now().timestamp();
-
-  let conn = pool
-    .get()
-    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
-
-  conn
-    .execute(
-      r#"
-      INSERT INTO app_settings(key, value, updated_at)
-      VALUES (?1, ?2, ?3)
-      ON CONFLICT(key) DO UPDATE SET
-        value = excluded.value,
-        updated_at = excluded.updated_at
-      "#,
-      params![KEY_API_KEYS, json, now],
-    )
-    .map_err(|e| AppError::Db(format!("save api_keys failed: {e}")))?;
+  // 表单里都是明文（get_api_keys 返回的是解密后的值），敏感字段全部加密后再落库，
+  // 不能按前缀跳过：用户填的 key 恰好以 "enc:v1:" 开头时会被当成密文、以明文落库
+  let mut stored = payload.clone();
+  for secret in stored.secrets_mut() {
+    if !secret.is_empty() {
+      *secret = crypto::encrypt(secret)?;
+    }
+  }
 
-  Ok(())
+  save_value(pool, KEY_API_KEYS, &stored)
 }
 
 pub fn get_api_keys(pool: &DbPool) -> Result<Option<ApiKeysForm>, AppError> {
@@ -51,8 +41,11 @@ pub fn get_api_keys(pool: &DbPool) -> Result<Option<ApiKeysForm>, AppError> {
 
   match row {
     Ok(json) => {
-      let data: ApiKeysForm =
+      let mut data: ApiKeysForm =
         serde_json::from_str(&json).map_err(|e| AppError::Serde(format!("from json failed: {e}")))?;
+      for secret in data.secrets_mut() {
+        *secret = crypto::decrypt(secret)?;
+      }
       Ok(Some(data))
     }
     Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -60,11 +53,103 @@ pub fn get_api_keys(pool: &DbPool) -> Result<Option<ApiKeysForm>, AppError> {
   }
 }
 
+/// 一次性迁移：把旧版本以明文保存的 API Keys 重新加密写回
+///
+/// 启动时调用；已经全部加密（或没有保存过）时什么也不做
+pub fn encrypt_legacy_api_keys(pool: &DbPool) -> Result<(), AppError> {
+  let conn = pool
+    .get()
+    .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
+
+  let row = conn.query_row(
+    "SELECT value FROM app_settings WHERE key = ?1",
+    params![KEY_API_KEYS],
+    |r| r.get::<_, String>(0),
+  );
+  drop(conn);
+
+  let json = match row {
+    Ok(json) => json,
+    Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(()),
+    Err(e) => return Err(AppError::Db(format!("query api_keys failed: {e}"))),
+  };
+
+  let mut stored: ApiKeysForm =
+    serde_json::from_str(&json).map_err(|e| AppError::Serde(format!("from json failed: {e}")))?;
+
+  let mut encrypted = 0;
+  for secret in stored.secrets_mut() {
+    if secret.is_empty() {
+      continue;
+    }
+    if !crypto::is_encrypted(secret) {
+      *secret = crypto::encrypt(secret)?;
+      encrypted += 1;
+    } else if crypto::decrypt(secret).is_err() {
+      // 以 "enc:v1:" 开头的历史明文和本机密钥丢失后的密文无法区分，保持原样：
+      // 读取时会报“本机密钥不匹配”，提示用户重新填写
+      log::warn!("api key looks encrypted but cannot be decrypted, leaving it unchanged");
+    }
+  }
+
+  if encrypted > 0 {
+    save_value(pool, KEY_API_KEYS, &stored)?;
+    log::info!("encrypted {encrypted} legacy plaintext api keys");
+  }
+
+  Ok(())
+}
+
 /* ==================== NETWORK PROXY ==================== */
 
 pub fn save_network(pool: &DbPool, payload: &NetworkProxyForm) -> Result<(), AppError> {
+  save_value(pool, KEY_NETWORK_PROXY, payload)
+}
+
+pub fn get_network(pool: &DbPool) -> Result<Option<NetworkProxyForm>, AppError> {
+  load_value(pool, KEY_NETWORK_PROXY)
+}
+
+pub fn save_network_overrides(
+  pool: &DbPool,
+  payload: &NetworkProxyOverrides,
+) -> Result<(), AppError> {
+  save_value(pool, KEY_NETWORK_OVERRIDES, payload)
+}
+
+pub fn get_network_overrides(pool: &DbPool) -> Result<NetworkProxyOverrides, AppError> {
+  Ok(load_value(pool, KEY_NETWORK_OVERRIDES)?.unwrap_or_default())
+}
+
+/// 某个服务实际生效的代理设置：优先使用按服务覆盖，否则回落到全局设置
+pub fn resolve_network(
+  pool: &DbPool,
+  service: ProxyService,
+) -> Result<Option<NetworkProxyForm>, AppError> {
+  if let Some(p) = get_network_overrides(pool)?.remove(&service) {
+    return Ok(Some(p));
+  }
+  get_network(pool)
+}
+
+/* ==================== DOC TRANSLATE ==================== */
+
+/// 文档翻译结果的下载目录；传 None 恢复默认（系统下载目录）
+pub fn save_doc_output_dir(pool: &DbPool, dir: Option<&str>) -> Result<(), AppError> {
+  let dir = dir.map(str::trim).filter(|d| !d.is_empty());
+  save_value(pool, KEY_DOC_OUTPUT_DIR, &dir)
+}
+
+pub fn get_doc_output_dir(pool: &DbPool) -> Result<Option<String>, AppError> {
+  Ok(load_value::<Option<String>>(pool, KEY_DOC_OUTPUT_DIR)?.flatten())
+}
+
+/* ==================== helpers ==================== */
+
+/// 以 JSON 形式写入 app_settings（存在则覆盖）
+fn save_value<T: Serialize>(pool: &DbPool, key: &str, value: &T) -> Result<(), AppError> {
   let json =
-    serde_json::to_string(payload).map_err(|e| AppError::Serde(format!("to json failed: {e}")))?;
+    serde_json::to_string(value).map_err(|e| AppError::Serde(format!("to json failed: {e}")))?;
 
   let now = Utc::now().timestamp();
 
@@ -81,14 +166,15 @@ pub fn save_network(pool: &DbPool, payload: &NetworkProxyForm) -> Result<(), App
         value = excluded.value,
         updated_at = excluded.updated_at
       "#,
-      params![KEY_NETWORK_PROXY, json, now],
+      params![key, json, now],
     )
-    .map_err(|e| AppError::Db(format!("save network_proxy failed: {e}")))?;
+    .map_err(|e| AppError::Db(format!("save {key} failed: {e}")))?;
 
   Ok(())
 }
 
-pub fn get_network(pool: &DbPool) -> Result<Option<NetworkProxyForm>, AppError> {
+/// 读取 app_settings 中的 JSON 值，不存在返回 None
+fn load_value<T: DeserializeOwned>(pool: &DbPool, key: &str) -> Result<Option<T>, AppError> {
   let conn = pool
     .get()
     .map_err(|e| AppError::Db(format!("db get conn failed: {e}")))?;
@@ -97,15 +183,107 @@ pub fn get_network(pool: &DbPool) -> Result<Option<NetworkProxyForm>, AppError>
     .prepare("SELECT value FROM app_settings WHERE key = ?1")
     .map_err(|e| AppError::Db(format!("prepare failed: {e}")))?;
 
-  let row = stmt.query_row(params![KEY_NETWORK_PROXY], |r| r.get::<_, String>(0));
+  let row = stmt.query_row(params![key], |r| r.get::<_, String>(0));
 
   match row {
     Ok(json) => {
-      let data: NetworkProxyForm = serde_json::from_str(&json)
+      let data: T = serde_json::from_str(&json)
         .map_err(|e| AppError::Serde(format!("from json failed: {e}")))?;
       Ok(Some(data))
     }
     Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-    Err(e) => Err(AppError::Db(format!("query network_proxy failed: {e}"))),
+    Err(e) => Err(AppError::Db(format!("query {key} failed: {e}"))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use r2d2_sqlite::SqliteConnectionManager;
+
+  /// 内存库只能有一个连接（每个连接都是独立的数据库）
+  fn pool() -> DbPool {
+    crypto::init_for_test();
+    let pool = r2d2::Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory())
+      .unwrap();
+    crate::db::migrate::migrate(&mut pool.get().unwrap()).unwrap();
+    pool
+  }
+
+  fn stored(pool: &DbPool) -> ApiKeysForm {
+    let json: String = pool
+      .get()
+      .unwrap()
+      .query_row("SELECT value FROM app_settings WHERE key = ?1", params![KEY_API_KEYS], |r| {
+        r.get(0)
+      })
+      .unwrap();
+    serde_json::from_str(&json).unwrap()
+  }
+
+  fn write_raw(pool: &DbPool, keys: &ApiKeysForm) {
+    save_value(pool, KEY_API_KEYS, keys).unwrap();
+  }
+
+  #[test]
+  fn api_keys_are_encrypted_at_rest() {
+    let pool = pool();
+    let mut keys = ApiKeysForm::default();
+    keys.translation.baidu.app_id = "app-id".to_string();
+    keys.translation.baidu.api_key = "ak".to_string();
+    keys.translation.deepl.api_key = "enc:v1:looks-encrypted".to_string();
+    save_api_keys(&pool, &keys).unwrap();
+
+    let raw = stored(&pool);
+    assert_eq!(raw.translation.baidu.app_id, "app-id");
+    assert!(crypto::is_encrypted(&raw.translation.baidu.api_key));
+    assert_ne!(raw.translation.deepl.api_key, "enc:v1:looks-encrypted");
+    assert_eq!(raw.translation.baidu.app_secret, "");
+
+    let loaded = get_api_keys(&pool).unwrap().unwrap();
+    assert_eq!(loaded.translation.baidu.api_key, "ak");
+    assert_eq!(loaded.translation.deepl.api_key, "enc:v1:looks-encrypted");
+  }
+
+  #[test]
+  fn legacy_plaintext_keys_are_encrypted() {
+    let pool = pool();
+    encrypt_legacy_api_keys(&pool).unwrap();
+
+    let mut keys = ApiKeysForm::default();
+    keys.translation.baidu.api_key = "ak".to_string();
+    keys.ai.openai.api_key = crypto::encrypt("sk-openai").unwrap();
+    write_raw(&pool, &keys);
+
+    encrypt_legacy_api_keys(&pool).unwrap();
+    let raw = stored(&pool);
+    assert!(crypto::is_encrypted(&raw.translation.baidu.api_key));
+    // 已加密的字段不会被二次加密
+    assert_eq!(raw.ai.openai.api_key, keys.ai.openai.api_key);
+
+    let loaded = get_api_keys(&pool).unwrap().unwrap();
+    assert_eq!(loaded.translation.baidu.api_key, "ak");
+    assert_eq!(loaded.ai.openai.api_key, "sk-openai");
+
+    // 再跑一次什么也不变
+    encrypt_legacy_api_keys(&pool).unwrap();
+    assert_eq!(stored(&pool).translation.baidu.api_key, raw.translation.baidu.api_key);
+  }
+
+  #[test]
+  fn undecryptable_prefixed_value_is_left_unchanged() {
+    let pool = pool();
+    let mut keys = ApiKeysForm::default();
+    keys.translation.baidu.api_key = "enc:v1:legacy-plaintext".to_string();
+    keys.translation.deepl.api_key = "plain".to_string();
+    write_raw(&pool, &keys);
+
+    encrypt_legacy_api_keys(&pool).unwrap();
+    let raw = stored(&pool);
+    assert_eq!(raw.translation.baidu.api_key, "enc:v1:legacy-plaintext");
+    assert!(crypto::is_encrypted(&raw.translation.deepl.api_key));
+    assert!(get_api_keys(&pool).is_err());
   }
 }
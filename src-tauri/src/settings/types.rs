@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -6,6 +8,26 @@ pub struct ApiKeysForm {
   pub ai: AiKeys,
 }
 
+impl ApiKeysForm {
+  /// 所有需要加密落库的敏感字段（appId、endpoint 等非敏感字段不在其中）
+  pub fn secrets_mut(&mut self) -> [&mut String; 10] {
+    let TranslationKeys { baidu, youdao, deepl } = &mut self.translation;
+    let AiKeys { openai, deepseek, qwen, doubao, wenxin, yuanbao } = &mut self.ai;
+    [
+      &mut baidu.api_key,
+      &mut baidu.app_secret,
+      &mut youdao.app_secret,
+      &mut deepl.api_key,
+      &mut openai.api_key,
+      &mut deepseek.api_key,
+      &mut qwen.api_key,
+      &mut doubao.api_key,
+      &mut wenxin.api_key,
+      &mut yuanbao.api_key,
+    ]
+  }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TranslationKeys {
   /// 百度翻译：需要 AppID / API Key / Secret Key
@@ -84,6 +106,25 @@ pub enum ProxyProtocol {
   Socks5,
 }
 
+/// 可以单独覆盖代理设置的服务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyService {
+  Baidu,
+  Youdao,
+  Deepl,
+  Github,
+  Openai,
+  Deepseek,
+  Qwen,
+  Doubao,
+  Wenxin,
+  Yuanbao,
+}
+
+/// 按服务覆盖的代理设置：没有出现在 map 里的服务使用全局设置
+pub type NetworkProxyOverrides = BTreeMap<ProxyService, NetworkProxyForm>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkProxyForm {
   pub mode: ProxyMode,
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use super::{TranslateContext, TranslateRequest, TranslationProvider};
+use crate::{
+  commands::baidu_translate::{self, TextTranslatePayload, TextTranslateResult},
+  error::AppError,
+  settings::ProxyService,
+};
+
+/// 百度通用文本翻译支持的语种（和前端 BAIDU_MT_LANGUAGES 保持一致）
+const LANGUAGES: &[&str] = &[
+  "ara", "gle", "oci", "alb", "arq", "aka", "arg", "amh", "asm", "aym", "aze", "ast", "oss", "est",
+  "oji", "ori", "orm", "pl", "per", "bre", "bak", "baq", "pot", "bel", "ber", "pam", "bul", "sme",
+  "ped", "bem", "bli", "bis", "bal", "ice", "bos", "bho", "chv", "tso", "dan", "de", "tat", "sha",
+  "tet", "div", "log", "ru", "fra", "fil", "fin", "san", "fri", "ful", "fao", "gla", "kon", "ups",
+  "hkm", "kal", "geo", "guj", "gra", "eno", "grn", "kor", "nl", "hup", "hak", "ht", "mot", "hau",
+  "kir", "glg", "frn", "cat", "cs", "kab", "kan", "kau", "kah", "cor", "xho", "cos", "cre", "cri",
+  "kli", "hrv", "que", "kas", "kok", "kur", "lat", "lao", "rom", "lag", "lav", "lim", "lin", "lug",
+  "ltz", "ruy", "kin", "lit", "roh", "ro", "loj", "may", "bur", "mar", "mg", "mal", "mac", "mah",
+  "mai", "glv", "mau", "mao", "ben", "mlt", "hmn", "nor", "nea", "nbl", "afr", "sot", "nep", "pt",
+  "pan", "pap", "pus", "nya", "twi", "chr", "jp", "swe", "srd", "sm", "sec", "srp", "sol", "sin",
+  "epo", "nob", "sk", "slo", "swa", "src", "som", "sco", "th", "tr", "tgk", "tam", "tgl", "tir",
+  "tel", "tua", "tuk", "ukr", "wln", "wel", "ven", "wol", "urd", "spa", "heb", "el", "hu", "fry",
+  "sil", "hil", "los", "haw", "nno", "nqo", "snd", "sna", "ceb", "syr", "sun", "en", "hi", "id",
+  "it", "vie", "yid", "ina", "ach", "ing", "ibo", "ido", "yor", "arm", "iku", "zh", "cht", "wyw",
+  "yue", "zaz", "frm", "zul", "jav",
+];
+
+pub struct BaiduProvider;
+
+#[async_trait]
+impl TranslationProvider for BaiduProvider {
+  async fn translate_text(
+    &self,
+    ctx: &TranslateContext<'_>,
+    req: &TranslateRequest,
+  ) -> Result<TextTranslateResult, AppError> {
+    // 统一语言代码就是百度的代码，不需要转换
+    let client = ctx.http.client(ctx.pool, ProxyService::Baidu).await?;
+    let payload = TextTranslatePayload {
+      q: req.q.clone(),
+      from: req.from.clone(),
+      to: req.to.clone(),
+      term_ids: req.term_ids.clone(),
+      no_cache: Some(req.no_cache),
+      glossary_id: None,
+    };
+
+    baidu_translate::text_translate(ctx.pool, &client, ctx.baidu_token, &payload).await
+  }
+
+  fn supported_languages(&self) -> Vec<&'static str> {
+    LANGUAGES.to_vec()
+  }
+}
@@ -0,0 +1,214 @@
+use crate::error::AppError;
+
+// ======= 批量翻译：打包 / 还原 =======
+// 多条文本按行拼成一个请求（"\n" 分隔），服务端按行分段返回，再按行号放回原位置。
+// - 空行不发送（百度会丢掉空行，导致分段数量对不上），还原时原样保留
+// - 单条文本里的换行同样按行处理，还原时再用 "\n" 拼回去
+
+/// 一次请求要发送的内容
+#[derive(Debug)]
+pub struct Chunk {
+  /// 用 "\n" 拼好的请求文本
+  pub text: String,
+  /// 每一行对应的 (条目下标, 行下标)，与服务端返回的分段一一对应
+  lines: Vec<(usize, usize)>,
+}
+
+#[derive(Debug)]
+pub struct BatchPlan {
+  /// 每条文本拆出的行（还原时在这上面替换译文）
+  items: Vec<Vec<String>>,
+  pub chunks: Vec<Chunk>,
+  /// 第 k 块完成后已经全部翻译完的条目数（用于进度）
+  done_items_after: Vec<usize>,
+}
+
+impl BatchPlan {
+  /// 把 items 打包成若干个不超过 max_bytes（UTF-8 字节数）的请求
+  pub fn new(items: &[String], max_bytes: usize) -> Result<Self, AppError> {
+    let items: Vec<Vec<String>> = items
+      .iter()
+      .map(|item| item.split('\n').map(|l| l.trim_end_matches('\r').to_string()).collect())
+      .collect();
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut current = Chunk {
+      text: String::new(),
+      lines: Vec::new(),
+    };
+    // 每个条目最后一行落在哪一块（没有需要翻译的行时为 None）
+    let mut last_chunk_of_item: Vec<Option<usize>> = vec![None; items.len()];
+
+    for (item_idx, lines) in items.iter().enumerate() {
+      for (line_idx, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+          continue;
+        }
+        if line.len() > max_bytes {
+          return Err(AppError::msg(format!(
+            "第 {} 条文本中有一行超过单次请求上限（{max_bytes} 字节），请拆分后再翻译",
+            item_idx + 1
+          )));
+        }
+
+        // 加上分隔用的 "\n" 会超限：先把当前块封口
+        let extra = if current.text.is_empty() { 0 } else { 1 };
+        if current.text.len() + extra + line.len() > max_bytes {
+          chunks.push(std::mem::replace(
+            &mut current,
+            Chunk {
+              text: String::new(),
+              lines: Vec::new(),
+            },
+          ));
+        }
+
+        if !current.text.is_empty() {
+          current.text.push('\n');
+        }
+        current.text.push_str(line);
+        current.lines.push((item_idx, line_idx));
+        last_chunk_of_item[item_idx] = Some(chunks.len());
+      }
+    }
+    if !current.lines.is_empty() {
+      chunks.push(current);
+    }
+
+    let done_items_after = (0..chunks.len())
+      .map(|k| {
+        last_chunk_of_item
+          .iter()
+          .filter(|last| match last {
+            Some(c) => *c <= k,
+            None => true,
+          })
+          .count()
+      })
+      .collect();
+
+    Ok(Self {
+      items,
+      chunks,
+      done_items_after,
+    })
+  }
+
+  pub fn total_items(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn done_items_after(&self, chunk_idx: usize) -> usize {
+    self.done_items_after[chunk_idx]
+  }
+
+  /// 把第 chunk_idx 块的译文（按行）写回对应条目
+  pub fn fill(&mut self, chunk_idx: usize, translated: Vec<String>) -> Result<(), AppError> {
+    let chunk = &self.chunks[chunk_idx];
+    if translated.len() != chunk.lines.len() {
+      return Err(AppError::msg(format!(
+        "译文分段数量（{}）与请求行数（{}）不一致",
+        translated.len(),
+        chunk.lines.len()
+      )));
+    }
+
+    for (&(item_idx, line_idx), dst) in chunk.lines.iter().zip(translated) {
+      self.items[item_idx][line_idx] = dst;
+    }
+    Ok(())
+  }
+
+  /// 按原顺序输出每条文本的译文
+  pub fn into_results(self) -> Vec<String> {
+    self.items.into_iter().map(|lines| lines.join("\n")).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn items(texts: &[&str]) -> Vec<String> {
+    texts.iter().map(|s| s.to_string()).collect()
+  }
+
+  /// 假装翻译：每行转成大写
+  fn translate_all(plan: &mut BatchPlan) {
+    for k in 0..plan.chunks.len() {
+      let translated = plan.chunks[k].text.split('\n').map(str::to_uppercase).collect();
+      plan.fill(k, translated).unwrap();
+    }
+  }
+
+  #[test]
+  fn packs_items_into_chunks_by_bytes() {
+    let plan = BatchPlan::new(&items(&["aaaa", "bbbb", "cccc"]), 9).unwrap();
+    let texts: Vec<&str> = plan.chunks.iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, ["aaaa\nbbbb", "cccc"]);
+    assert!(plan.chunks.iter().all(|c| c.text.len() <= 9));
+
+    // 刚好等于上限的单行也能发
+    let plan = BatchPlan::new(&items(&["123456789"]), 9).unwrap();
+    assert_eq!(plan.chunks.len(), 1);
+  }
+
+  #[test]
+  fn oversized_line_is_error() {
+    let err = BatchPlan::new(&items(&["ok", "0123456789"]), 9).unwrap_err();
+    assert!(err.to_string().contains("第 2 条"), "{err}");
+
+    // 按字节而不是字符计算
+    assert!(BatchPlan::new(&items(&["中文字"]), 8).is_err());
+    // 单条文本总长超限但每行都不超：拆到多块
+    let plan = BatchPlan::new(&items(&["aaaa\nbbbb\ncccc"]), 9).unwrap();
+    assert_eq!(plan.chunks.len(), 2);
+  }
+
+  #[test]
+  fn newlines_and_blank_lines_round_trip() {
+    let mut plan = BatchPlan::new(&items(&["line1\r\nline2", "", "a\n\n  \nb\n"]), 100).unwrap();
+    assert_eq!(plan.chunks.len(), 1);
+    assert_eq!(plan.chunks[0].text, "line1\nline2\na\nb");
+
+    translate_all(&mut plan);
+    assert_eq!(plan.into_results(), ["LINE1\nLINE2", "", "A\n\n  \nB\n"]);
+  }
+
+  #[test]
+  fn results_keep_item_order_across_chunks() {
+    let texts = ["one", "two", "three", "four", "five"];
+    let mut plan = BatchPlan::new(&items(&texts), 8).unwrap();
+    assert!(plan.chunks.len() > 1);
+
+    translate_all(&mut plan);
+    assert_eq!(plan.into_results(), ["ONE", "TWO", "THREE", "FOUR", "FIVE"]);
+  }
+
+  #[test]
+  fn fill_rejects_segment_count_mismatch() {
+    let mut plan = BatchPlan::new(&items(&["a\nb"]), 100).unwrap();
+    assert!(plan.fill(0, vec!["A".to_string()]).is_err());
+    assert!(plan.fill(0, vec!["A".to_string(), "B".to_string(), "C".to_string()]).is_err());
+    plan.fill(0, vec!["A".to_string(), "B".to_string()]).unwrap();
+    assert_eq!(plan.into_results(), ["A\nB"]);
+  }
+
+  #[test]
+  fn progress_counts_finished_items() {
+    // 块："aaaa\nbbbb"、"cccc\ndddd"、"eeee"；第 3 条跨了前两块，空条目一开始就算完成
+    let plan = BatchPlan::new(&items(&["aaaa", "", "bbbb\ncccc", "dddd", "eeee"]), 9).unwrap();
+    assert_eq!(plan.total_items(), 5);
+    assert_eq!(plan.chunks.len(), 3);
+    assert_eq!(plan.done_items_after(0), 2);
+    assert_eq!(plan.done_items_after(1), 4);
+    assert_eq!(plan.done_items_after(2), 5);
+  }
+
+  #[test]
+  fn all_blank_items_need_no_requests() {
+    let plan = BatchPlan::new(&items(&["", "  ", "\n"]), 100).unwrap();
+    assert!(plan.chunks.is_empty());
+    assert_eq!(plan.into_results(), ["", "  ", "\n"]);
+  }
+}
@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+
+use super::{from_native, to_native, LangMap, TranslateContext, TranslateRequest, TranslationProvider};
+use crate::{
+  commands::{
+    baidu_translate::TextTranslateResult,
+    deepl_translate::{self, DeeplTranslatePayload},
+  },
+  error::AppError,
+  settings::ProxyService,
+};
+
+/// 统一语言代码（百度）-> DeepL 语言代码
+///
+/// 英语/葡萄牙语作为目标语言时 DeepL 要求带地区，见 target_code
+const LANG_MAP: LangMap = &[
+  ("zh", "ZH"),
+  ("cht", "ZH-HANT"),
+  ("en", "EN"),
+  ("jp", "JA"),
+  ("kor", "KO"),
+  ("fra", "FR"),
+  ("spa", "ES"),
+  ("de", "DE"),
+  ("ru", "RU"),
+  ("pt", "PT"),
+  ("it", "IT"),
+  ("nl", "NL"),
+  ("pl", "PL"),
+  ("swe", "SV"),
+  ("dan", "DA"),
+  ("fin", "FI"),
+  ("el", "EL"),
+  ("cs", "CS"),
+  ("bul", "BG"),
+  ("est", "ET"),
+  ("hu", "HU"),
+  ("lav", "LV"),
+  ("lit", "LT"),
+  ("rom", "RO"),
+  ("sk", "SK"),
+  ("slo", "SL"),
+  ("tr", "TR"),
+  ("ukr", "UK"),
+  ("id", "ID"),
+  ("nor", "NB"),
+  ("ara", "AR"),
+];
+
+pub struct DeeplProvider;
+
+#[async_trait]
+impl TranslationProvider for DeeplProvider {
+  async fn translate_text(
+    &self,
+    ctx: &TranslateContext<'_>,
+    req: &TranslateRequest,
+  ) -> Result<TextTranslateResult, AppError> {
+    let client = ctx.http.client(ctx.pool, ProxyService::Deepl).await?;
+    let key = deepl_translate::get_deepl_key(ctx.pool)?;
+    let payload = DeeplTranslatePayload {
+      q: req.q.clone(),
+      from: (req.from != "auto").then(|| to_native(LANG_MAP, &req.from)),
+      to: target_code(&req.to),
+      formality: None,
+      glossary_id: None,
+    };
+
+    let mut result = deepl_translate::text_translate(&client, &key, &payload).await?;
+    result.from = from_native(LANG_MAP, &result.from);
+    result.to = req.to.clone();
+    Ok(result)
+  }
+
+  fn supported_languages(&self) -> Vec<&'static str> {
+    LANG_MAP.iter().map(|(code, _)| *code).collect()
+  }
+}
+
+/// EN / PT 作为目标语言已废弃，需要指定地区
+fn target_code(code: &str) -> String {
+  match code {
+    "en" => "EN-US".to_string(),
+    "pt" => "PT-PT".to_string(),
+    _ => to_native(LANG_MAP, code),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::translate::{provider, ProviderKind};
+
+  #[test]
+  fn lang_map_round_trips() {
+    let baidu = provider(ProviderKind::Baidu).supported_languages();
+    for (unified, native) in LANG_MAP {
+      assert!(baidu.contains(unified), "{unified} is not a Baidu code");
+      assert_eq!(to_native(LANG_MAP, unified), *native);
+      assert_eq!(from_native(LANG_MAP, native), *unified, "{native}");
+      assert_eq!(from_native(LANG_MAP, &native.to_ascii_lowercase()), *unified);
+    }
+  }
+
+  #[test]
+  fn slovak_and_slovenian() {
+    assert_eq!(to_native(LANG_MAP, "sk"), "SK");
+    assert_eq!(to_native(LANG_MAP, "slo"), "SL");
+    assert_eq!(target_code("en"), "EN-US");
+    assert_eq!(target_code("pt"), "PT-PT");
+    assert_eq!(target_code("slo"), "SL");
+  }
+}
@@ -0,0 +1,170 @@
+mod baidu;
+pub mod batch;
+mod deepl;
+pub mod upload;
+mod youdao;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  commands::baidu_translate::{BaiduTokenState, TextTranslateResult},
+  db::DbPool,
+  error::AppError,
+  http::HttpClientState,
+  settings::TranslationKeys,
+};
+
+/// 已接入的翻译服务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+  Baidu,
+  Youdao,
+  Deepl,
+}
+
+impl ProviderKind {
+  pub const ALL: [ProviderKind; 3] = [ProviderKind::Baidu, ProviderKind::Youdao, ProviderKind::Deepl];
+
+  /// 与 serde 序列化结果一致，用于落库
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ProviderKind::Baidu => "baidu",
+      ProviderKind::Youdao => "youdao",
+      ProviderKind::Deepl => "deepl",
+    }
+  }
+
+  /// 设置里是否已经填好了这个服务需要的密钥
+  pub fn is_configured(self, keys: &TranslationKeys) -> bool {
+    match self {
+      ProviderKind::Baidu => !keys.baidu.api_key.is_empty() && !keys.baidu.app_secret.is_empty(),
+      ProviderKind::Youdao => !keys.youdao.app_id.is_empty() && !keys.youdao.app_secret.is_empty(),
+      ProviderKind::Deepl => !keys.deepl.api_key.trim().is_empty(),
+    }
+  }
+}
+
+/// 统一的文本翻译请求
+///
+/// 语言代码统一使用百度的语种代码（前端语种列表的唯一真源），
+/// 各 provider 内部再转换成自己的代码；没有映射的代码原样透传
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslateRequest {
+  pub q: String,
+  pub from: String, // 可传 "auto"
+  pub to: String,
+  /// 本地术语表，见 translate()
+  #[serde(default)]
+  pub glossary_id: Option<i64>,
+  /// 百度术语库 ID：百度命令显式传入，translate() 再合并术语表关联的术语库；其他 provider 忽略
+  #[serde(skip)]
+  pub term_ids: Option<String>,
+  /// 跳过本地缓存（目前只有百度文本翻译有缓存）
+  #[serde(skip)]
+  pub no_cache: bool,
+}
+
+/// provider 执行请求时需要的共享状态（都来自 Tauri managed state）
+pub struct TranslateContext<'a> {
+  pub pool: &'a DbPool,
+  pub http: &'a HttpClientState,
+  pub baidu_token: &'a BaiduTokenState,
+}
+
+#[async_trait]
+pub trait TranslationProvider: Send + Sync {
+  /// 文本翻译；返回结果里的 from / to 已转换回统一语言代码
+  async fn translate_text(
+    &self,
+    ctx: &TranslateContext<'_>,
+    req: &TranslateRequest,
+  ) -> Result<TextTranslateResult, AppError>;
+
+  /// 语种检测：默认用 from=auto 翻译一次，取服务端识别出的源语言
+  async fn detect_language(&self, ctx: &TranslateContext<'_>, q: &str) -> Result<String, AppError> {
+    let req = TranslateRequest {
+      q: q.to_string(),
+      from: "auto".to_string(),
+      to: "en".to_string(),
+      glossary_id: None,
+      term_ids: None,
+      no_cache: false,
+    };
+    let result = self.translate_text(ctx, &req).await?;
+    if result.from.is_empty() || result.from == "auto" {
+      return Err(AppError::msg("无法识别语种"));
+    }
+    Ok(result.from)
+  }
+
+  /// 支持的语言（统一语言代码，不含 "auto"）
+  fn supported_languages(&self) -> Vec<&'static str>;
+}
+
+/// 按 kind 取 provider（都是无状态的单例）
+pub fn provider(kind: ProviderKind) -> &'static dyn TranslationProvider {
+  match kind {
+    ProviderKind::Baidu => &baidu::BaiduProvider,
+    ProviderKind::Youdao => &youdao::YoudaoProvider,
+    ProviderKind::Deepl => &deepl::DeeplProvider,
+  }
+}
+
+/// 带术语表的统一翻译入口（命令层都走这里，不直接调 provider）
+///
+/// - 没选术语表：直接交给 provider
+/// - 百度 + 术语表关联了百度术语库：透传 term_ids，由服务端干预
+/// - 其他情况：客户端强制术语（见 SelectedGlossary::enforce）
+pub async fn translate(
+  ctx: &TranslateContext<'_>,
+  kind: ProviderKind,
+  req: &TranslateRequest,
+) -> Result<TextTranslateResult, AppError> {
+  let provider = provider(kind);
+  let Some(glossary_id) = req.glossary_id else {
+    return provider.translate_text(ctx, req).await;
+  };
+
+  let glossary = crate::glossary::select(ctx.pool, glossary_id, &req.from, &req.to)?;
+  if kind == ProviderKind::Baidu {
+    if let Some(term_ids) = glossary.baidu_term_ids(req.term_ids.as_deref()) {
+      let req = TranslateRequest {
+        term_ids: Some(term_ids),
+        ..req.clone()
+      };
+      return provider.translate_text(ctx, &req).await;
+    }
+  }
+
+  glossary
+    .enforce(&req.q, |q| {
+      let req = TranslateRequest {
+        q,
+        glossary_id: None,
+        ..req.clone()
+      };
+      async move { provider.translate_text(ctx, &req).await }
+    })
+    .await
+}
+
+/// 统一语言代码 -> provider 语言代码 的映射表
+type LangMap = &'static [(&'static str, &'static str)];
+
+fn to_native(map: LangMap, code: &str) -> String {
+  map
+    .iter()
+    .find(|(unified, _)| *unified == code)
+    .map(|(_, native)| native.to_string())
+    .unwrap_or_else(|| code.to_string())
+}
+
+fn from_native(map: LangMap, code: &str) -> String {
+  map
+    .iter()
+    .find(|(_, native)| native.eq_ignore_ascii_case(code))
+    .map(|(unified, _)| unified.to_string())
+    .unwrap_or_else(|| code.to_string())
+}
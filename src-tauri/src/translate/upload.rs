@@ -0,0 +1,719 @@
+// ======= 上传文件读取 =======
+// 文档 / 图片翻译由前端传文件路径，这里在发请求前读文件并按百度的限制校验，
+// 避免几十 MB 的文件以 JSON 数字数组的形式走 IPC，也避免不合格的文件只换回一个看不懂的接口错误
+// （旧的调用方式直接传文件内容，仍然兼容，校验规则相同）
+
+use std::path::Path;
+
+use crate::error::{AppError, UploadError};
+
+/// 文档翻译：单个文件不超过 50 MB
+pub const DOC_MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// 图片翻译：图片不超过 4 MB
+pub const PIC_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 文档翻译支持的输入格式（小写扩展名）
+pub const DOC_FORMATS: &[&str] = &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "wps"];
+
+/// 输入格式 -> 允许的 output_format（第一个是服务端默认输出）
+const DOC_OUTPUT_FORMATS: &[(&str, &[&str])] = &[
+  ("pdf", &["docx", "pdf"]),
+  ("doc", &["docx", "pdf"]),
+  ("docx", &["docx", "pdf"]),
+  ("wps", &["docx", "pdf"]),
+  ("xls", &["xlsx"]),
+  ("xlsx", &["xlsx"]),
+  ("ppt", &["pptx"]),
+  ("pptx", &["pptx"]),
+  ("txt", &["txt"]),
+];
+
+/// 图片翻译支持的格式：扩展名 -> mime
+const PIC_FORMATS: &[(&str, &str)] = &[
+  ("png", "image/png"),
+  ("jpg", "image/jpeg"),
+  ("jpeg", "image/jpeg"),
+  ("webp", "image/webp"),
+];
+
+/// OOXML 各类型的主文档部件 -> 扩展名
+const OOXML_MAIN_PARTS: &[(&str, &str)] = &[
+  ("word/document.xml", "docx"),
+  ("xl/workbook.xml", "xlsx"),
+  ("ppt/presentation.xml", "pptx"),
+];
+
+/// 剪贴板图片编码后超限时最多缩小几次
+const PNG_SHRINK_ATTEMPTS: usize = 3;
+
+/// 文本文件判定时检查的前缀长度
+const TEXT_SNIFF_BYTES: usize = 8192;
+
+/// 按魔数识别出的文档内容类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocContent {
+  Pdf,
+  /// Office Open XML（zip 包）；能从包内目录判断出具体类型时带上扩展名
+  Ooxml(Option<&'static str>),
+  /// OLE2 复合文档：doc / xls / ppt / wps 都是这种容器，内容上不再细分
+  Ole2,
+  Text,
+}
+
+impl DocContent {
+  pub fn detect(bytes: &[u8]) -> Option<Self> {
+    if bytes.starts_with(b"%PDF-") {
+      return Some(DocContent::Pdf);
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+      // 按 zip 中央目录里的主文档部件判断具体类型；只看部件全名，
+      // 避免 pptx 里嵌入的 Excel 图表（ppt/embeddings/..xl/..）之类的误判
+      let names = zip_entry_names(bytes);
+      let subtype = OOXML_MAIN_PARTS
+        .iter()
+        .find(|(part, _)| names.iter().any(|n| n == part))
+        .map(|(_, ext)| *ext);
+      return Some(DocContent::Ooxml(subtype));
+    }
+    if bytes.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+      return Some(DocContent::Ole2);
+    }
+    if is_text(bytes) {
+      return Some(DocContent::Text);
+    }
+    None
+  }
+
+  /// 用于错误提示
+  fn label(self) -> &'static str {
+    match self {
+      DocContent::Pdf => "PDF",
+      DocContent::Ooxml(Some("docx")) => "Word（docx）",
+      DocContent::Ooxml(Some("xlsx")) => "Excel（xlsx）",
+      DocContent::Ooxml(Some("pptx")) => "PowerPoint（pptx）",
+      DocContent::Ooxml(_) => "zip 压缩包",
+      DocContent::Ole2 => "旧版 Office 文档（doc / xls / ppt / wps）",
+      DocContent::Text => "纯文本",
+    }
+  }
+
+  /// 内容是否可能是声明的格式
+  fn matches(self, format: &str) -> bool {
+    match self {
+      DocContent::Pdf => format == "pdf",
+      DocContent::Ooxml(Some(ext)) => format == ext,
+      DocContent::Ooxml(None) => matches!(format, "docx" | "xlsx" | "pptx"),
+      DocContent::Ole2 => matches!(format, "doc" | "xls" | "ppt" | "wps"),
+      DocContent::Text => format == "txt",
+    }
+  }
+
+  /// 没有声明格式时能否直接确定扩展名（OLE2 没法细分）
+  fn format(self) -> Option<&'static str> {
+    match self {
+      DocContent::Pdf => Some("pdf"),
+      DocContent::Ooxml(ext) => ext,
+      DocContent::Ole2 => None,
+      DocContent::Text => Some("txt"),
+    }
+  }
+}
+
+/// 待上传文件的来源
+#[derive(Debug)]
+pub enum UploadSource {
+  /// 文件路径，由后端读取
+  Path(String),
+  /// 前端直接传来的文件内容（filename 用于判断扩展名）
+  Bytes { bytes: Vec<u8>, filename: Option<String> },
+}
+
+impl UploadSource {
+  /// path 优先；两者都没有时报错
+  pub fn new(
+    path: Option<String>,
+    bytes: Option<Vec<u8>>,
+    filename: Option<String>,
+  ) -> Result<Self, UploadError> {
+    match (path.filter(|p| !p.trim().is_empty()), bytes) {
+      (Some(path), _) => Ok(UploadSource::Path(path)),
+      (None, Some(bytes)) => Ok(UploadSource::Bytes { bytes, filename }),
+      (None, None) => Err(UploadError::NoInput),
+    }
+  }
+
+  fn filename(&self) -> String {
+    match self {
+      UploadSource::Path(path) => filename(Path::new(path)),
+      UploadSource::Bytes { filename: name, .. } => {
+        name.as_deref().map(|n| filename(Path::new(n))).unwrap_or_default()
+      }
+    }
+  }
+
+  fn extension(&self) -> Option<String> {
+    match self {
+      UploadSource::Path(path) => extension(Path::new(path)),
+      UploadSource::Bytes { filename, .. } => filename.as_deref().and_then(|n| extension(Path::new(n))),
+    }
+  }
+
+  /// 先检查大小再读，超限的文件不读进内存
+  async fn read(self, max_bytes: u64) -> Result<Vec<u8>, AppError> {
+    match self {
+      UploadSource::Path(path) => read_limited(Path::new(&path), max_bytes).await,
+      UploadSource::Bytes { bytes, filename } => {
+        let display = filename.unwrap_or_else(|| "上传的文件".to_string());
+        check_size(display, bytes.len() as u64, max_bytes)?;
+        Ok(bytes)
+      }
+    }
+  }
+}
+
+/// 读好并通过校验的待上传文件
+#[derive(Debug)]
+pub struct UploadFile {
+  /// 不含目录的文件名
+  pub filename: String,
+  /// 文档是格式（"docx"），图片是 mime（"image/png"）
+  pub format: String,
+  pub bytes: Vec<u8>,
+}
+
+/// 读取待翻译的文档并校验：大小、格式（声明 / 扩展名 / 文件内容三者一致）、输出格式组合
+///
+/// format 不传时按扩展名判断，扩展名也没有时按文件内容判断
+pub async fn read_doc(
+  source: UploadSource,
+  format: Option<&str>,
+  output_format: Option<&str>,
+) -> Result<UploadFile, AppError> {
+  let declared = format
+    .map(str::trim)
+    .filter(|f| !f.is_empty())
+    .map(str::to_ascii_lowercase)
+    .or_else(|| source.extension());
+
+  if let Some(format) = &declared {
+    check_doc_format(format)?;
+  }
+
+  let filename = source.filename();
+  let bytes = source.read(DOC_MAX_BYTES).await?;
+  let format = check_doc_content(&bytes, declared)?;
+  if let Some(output) = output_format {
+    check_output_format(&format, output)?;
+  }
+
+  Ok(UploadFile {
+    filename,
+    format,
+    bytes,
+  })
+}
+
+/// 读取待翻译的图片；mime 不传时按扩展名判断
+pub async fn read_image(source: UploadSource, mime: Option<&str>) -> Result<UploadFile, AppError> {
+  let supported = || "png / jpg / webp".to_string();
+  let mime = match mime.map(str::trim).filter(|m| !m.is_empty()) {
+    Some(m) => m.to_ascii_lowercase(),
+    None => {
+      let ext = source.extension().unwrap_or_default();
+      PIC_FORMATS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, mime)| mime.to_string())
+        .ok_or(UploadError::UnsupportedFormat {
+          format: ext,
+          supported: supported(),
+        })?
+    }
+  };
+  if !PIC_FORMATS.iter().any(|(_, m)| *m == mime) {
+    return Err(
+      UploadError::UnsupportedFormat {
+        format: mime,
+        supported: supported(),
+      }
+      .into(),
+    );
+  }
+
+  let filename = Some(source.filename())
+    .filter(|n| !n.is_empty())
+    .unwrap_or_else(|| "image".to_string());
+  let bytes = source.read(PIC_MAX_BYTES).await?;
+  Ok(UploadFile {
+    filename,
+    format: mime,
+    bytes,
+  })
+}
+
+/// 剪贴板里的原始 RGBA 像素编码成 PNG；超过图片大小上限时按比例缩小后重试
+pub fn encode_png(rgba: Vec<u8>, width: u32, height: u32) -> Result<UploadFile, AppError> {
+  let mut img = image::RgbaImage::from_raw(width, height, rgba)
+    .ok_or_else(|| AppError::msg("剪贴板图片数据不完整"))?;
+
+  let mut size = 0;
+  for _ in 0..PNG_SHRINK_ATTEMPTS {
+    let mut buf = std::io::Cursor::new(Vec::new());
+    img
+      .write_to(&mut buf, image::ImageFormat::Png)
+      .map_err(|e| AppError::Serde(format!("encode png failed: {e}")))?;
+    let bytes = buf.into_inner();
+
+    size = bytes.len() as u64;
+    if size <= PIC_MAX_BYTES {
+      return Ok(UploadFile {
+        filename: "clipboard.png".to_string(),
+        format: "image/png".to_string(),
+        bytes,
+      });
+    }
+
+    // 体积大致和像素数成正比，边长按 sqrt(上限 / 实际) 缩，再多留 10% 余量
+    let scale = (PIC_MAX_BYTES as f64 / size as f64).sqrt() * 0.9;
+    let (w, h) = img.dimensions();
+    img = image::imageops::thumbnail(
+      &img,
+      ((w as f64 * scale) as u32).max(1),
+      ((h as f64 * scale) as u32).max(1),
+    );
+  }
+
+  Err(
+    UploadError::TooLarge {
+      path: "剪贴板图片".to_string(),
+      size,
+      max: PIC_MAX_BYTES,
+    }
+    .into(),
+  )
+}
+
+fn check_doc_format(format: &str) -> Result<(), UploadError> {
+  if DOC_FORMATS.contains(&format) {
+    return Ok(());
+  }
+  Err(UploadError::UnsupportedFormat {
+    format: format.to_string(),
+    supported: DOC_FORMATS.join(" / "),
+  })
+}
+
+/// 按魔数核对文件内容，返回最终使用的格式
+fn check_doc_content(bytes: &[u8], declared: Option<String>) -> Result<String, UploadError> {
+  let detected = DocContent::detect(bytes);
+
+  match (declared, detected) {
+    (Some(declared), Some(content)) if !content.matches(&declared) => Err(UploadError::FormatMismatch {
+      declared,
+      detected: content.label(),
+    }),
+    (Some(declared), Some(_)) => Ok(declared),
+    // 只有 txt 可能认不出来（例如 GBK 编码），以声明为准交给服务端判断；
+    // 其他格式都有固定魔数，认不出来说明文件已损坏或根本不是这种格式
+    (Some(declared), None) if declared == "txt" => Ok(declared),
+    (Some(declared), None) => Err(UploadError::FormatMismatch {
+      declared,
+      detected: "无法识别的内容",
+    }),
+    (None, Some(content)) => content
+      .format()
+      .map(str::to_string)
+      .ok_or(UploadError::UnknownContent),
+    (None, None) => Err(UploadError::UnknownContent),
+  }
+}
+
+fn check_output_format(input: &str, output: &str) -> Result<(), UploadError> {
+  let output = output.trim().to_ascii_lowercase();
+  let allowed = DOC_OUTPUT_FORMATS
+    .iter()
+    .find(|(i, _)| *i == input)
+    .map(|(_, o)| *o)
+    .unwrap_or_default();
+
+  if allowed.contains(&output.as_str()) {
+    return Ok(());
+  }
+  Err(UploadError::UnsupportedOutput {
+    input: input.to_string(),
+    output,
+    allowed: allowed.join(" / "),
+  })
+}
+
+async fn read_limited(path: &Path, max_bytes: u64) -> Result<Vec<u8>, AppError> {
+  let meta = tokio::fs::metadata(path)
+    .await
+    .map_err(|e| AppError::Io(format!("read {} failed: {e}", path.display())))?;
+  let display = path.display().to_string();
+  if !meta.is_file() {
+    return Err(UploadError::NotAFile(display).into());
+  }
+  check_size(display, meta.len(), max_bytes)?;
+
+  tokio::fs::read(path)
+    .await
+    .map_err(|e| AppError::Io(format!("read {} failed: {e}", path.display())))
+}
+
+fn check_size(display: String, size: u64, max_bytes: u64) -> Result<(), UploadError> {
+  if size == 0 {
+    return Err(UploadError::Empty(display));
+  }
+  if size > max_bytes {
+    return Err(UploadError::TooLarge {
+      path: display,
+      size,
+      max: max_bytes,
+    });
+  }
+  Ok(())
+}
+
+/// UTF-8（允许截断在多字节字符中间）或带 BOM 的 UTF-16，且不含 NUL
+fn is_text(bytes: &[u8]) -> bool {
+  if bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]) {
+    return true;
+  }
+
+  let head = &bytes[..bytes.len().min(TEXT_SNIFF_BYTES)];
+  if head.contains(&0) {
+    return false;
+  }
+  match std::str::from_utf8(head) {
+    Ok(_) => true,
+    // 只在结尾处截断了一个字符
+    Err(e) => e.error_len().is_none() && head.len() == TEXT_SNIFF_BYTES,
+  }
+}
+
+/// 从 zip 末尾的中央目录读出全部文件名；结构不完整（截断、zip64 等）时返回空
+fn zip_entry_names(bytes: &[u8]) -> Vec<String> {
+  const EOCD_SIG: &[u8] = b"PK\x05\x06";
+  const CDFH_SIG: &[u8] = b"PK\x01\x02";
+  // EOCD 固定 22 字节，后面可以跟最长 65535 字节的注释
+  const EOCD_LEN: usize = 22;
+  const EOCD_SEARCH: usize = EOCD_LEN + u16::MAX as usize;
+  const CDFH_LEN: usize = 46;
+
+  let u16_at = |at: usize| bytes.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+  let u32_at = |at: usize| {
+    bytes
+      .get(at..at + 4)
+      .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+  };
+
+  let search_from = bytes.len().saturating_sub(EOCD_SEARCH);
+  let Some(eocd) = (search_from..=bytes.len().saturating_sub(EOCD_LEN))
+    .rev()
+    .find(|&i| bytes[i..].starts_with(EOCD_SIG))
+  else {
+    return Vec::new();
+  };
+  let (Some(count), Some(mut offset)) = (u16_at(eocd + 10), u32_at(eocd + 16)) else {
+    return Vec::new();
+  };
+
+  let mut names = Vec::with_capacity(count);
+  for _ in 0..count {
+    if !bytes.get(offset..).is_some_and(|b| b.starts_with(CDFH_SIG)) {
+      break;
+    }
+    let (Some(name_len), Some(extra_len), Some(comment_len)) =
+      (u16_at(offset + 28), u16_at(offset + 30), u16_at(offset + 32))
+    else {
+      break;
+    };
+    let name_start = offset + CDFH_LEN;
+    let Some(name) = bytes.get(name_start..name_start + name_len) else {
+      break;
+    };
+    names.push(String::from_utf8_lossy(name).into_owned());
+    offset = name_start + name_len + extra_len + comment_len;
+  }
+  names
+}
+
+fn extension(path: &Path) -> Option<String> {
+  path
+    .extension()
+    .and_then(|e| e.to_str())
+    .map(str::to_ascii_lowercase)
+}
+
+fn filename(path: &Path) -> String {
+  path
+    .file_name()
+    .map(|n| n.to_string_lossy().into_owned())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// 拼一个只有目录结构的 zip：每个条目一个本地文件头 + 内容，末尾是中央目录和 EOCD
+  fn zip_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in entries {
+      let offset = out.len() as u32;
+      out.extend_from_slice(b"PK\x03\x04");
+      out.extend_from_slice(&[0; 22]);
+      out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+      out.extend_from_slice(&0u16.to_le_bytes());
+      out.extend_from_slice(name.as_bytes());
+      out.extend_from_slice(data);
+
+      central.extend_from_slice(b"PK\x01\x02");
+      central.extend_from_slice(&[0; 24]);
+      central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+      central.extend_from_slice(&[0; 12]);
+      central.extend_from_slice(&offset.to_le_bytes());
+      central.extend_from_slice(name.as_bytes());
+    }
+
+    let cd_offset = out.len() as u32;
+    let cd_size = central.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(b"PK\x05\x06");
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&cd_size.to_le_bytes());
+    out.extend_from_slice(&cd_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+  }
+
+  #[test]
+  fn detect_ooxml_by_main_part() {
+    let docx = zip_with(&[("[Content_Types].xml", b"<Types/>"), ("word/document.xml", b"<w:document/>")]);
+    assert_eq!(DocContent::detect(&docx), Some(DocContent::Ooxml(Some("docx"))));
+
+    let xlsx = zip_with(&[("xl/workbook.xml", b""), ("xl/worksheets/sheet1.xml", b"")]);
+    assert_eq!(DocContent::detect(&xlsx), Some(DocContent::Ooxml(Some("xlsx"))));
+
+    // 嵌了 Excel 图表、正文里还提到 word/ 的 pptx 仍然是 pptx
+    let pptx = zip_with(&[
+      ("ppt/slides/slide1.xml", b"see word/document.xml and xl/"),
+      ("ppt/embeddings/Microsoft_Excel_Worksheet.xlsx", b"PK\x03\x04xl/workbook.xml"),
+      ("ppt/presentation.xml", b""),
+    ]);
+    assert_eq!(DocContent::detect(&pptx), Some(DocContent::Ooxml(Some("pptx"))));
+
+    let plain_zip = zip_with(&[("readme.txt", b"word/ xl/ ppt/")]);
+    assert_eq!(DocContent::detect(&plain_zip), Some(DocContent::Ooxml(None)));
+  }
+
+  #[test]
+  fn detect_truncated_zip_has_no_subtype() {
+    let docx = zip_with(&[("word/document.xml", b"")]);
+    let truncated = &docx[..docx.len() - 10];
+    assert_eq!(DocContent::detect(truncated), Some(DocContent::Ooxml(None)));
+    assert_eq!(DocContent::detect(b"PK\x03\x04"), Some(DocContent::Ooxml(None)));
+  }
+
+  #[test]
+  fn detect_other_formats() {
+    assert_eq!(DocContent::detect(b"%PDF-1.7\n..."), Some(DocContent::Pdf));
+    assert_eq!(
+      DocContent::detect(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0, 0]),
+      Some(DocContent::Ole2)
+    );
+    assert_eq!(DocContent::detect("你好，world\n".as_bytes()), Some(DocContent::Text));
+    assert_eq!(DocContent::detect(&[0x00, 0x01, 0x02]), None);
+  }
+
+  #[test]
+  fn is_text_cases() {
+    assert!(is_text(b"hello"));
+    assert!(is_text(&[0xFF, 0xFE, b'h', 0]));
+    assert!(is_text(&[0xFE, 0xFF, 0, b'h']));
+    assert!(!is_text(b"hel\0lo"));
+    assert!(!is_text(&[0xC3, 0x28]));
+
+    // 在多字节字符中间截断：只有刚好截在嗅探长度上才算文本
+    let mut long = "a".repeat(TEXT_SNIFF_BYTES - 1).into_bytes();
+    long.extend_from_slice("中".as_bytes());
+    assert!(is_text(&long));
+    assert!(!is_text(&"中".as_bytes()[..2]));
+  }
+
+  #[test]
+  fn check_doc_content_cases() {
+    let docx = zip_with(&[("word/document.xml", b"")]);
+    let ole = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+    assert_eq!(check_doc_content(&docx, Some("docx".into())).unwrap(), "docx");
+    assert_eq!(check_doc_content(&docx, None).unwrap(), "docx");
+    assert_eq!(check_doc_content(b"%PDF-1.4", None).unwrap(), "pdf");
+    assert_eq!(check_doc_content(b"plain", None).unwrap(), "txt");
+    assert_eq!(check_doc_content(&ole, Some("wps".into())).unwrap(), "wps");
+    // 认不出的内容只有声明为 txt 时放行（GBK 文本等）
+    let gbk = [0xC4, 0xE3, 0xBA, 0xC3];
+    assert_eq!(check_doc_content(&gbk, Some("txt".into())).unwrap(), "txt");
+    assert_eq!(check_doc_content(&[0, 1, 2], Some("txt".into())).unwrap(), "txt");
+    for declared in ["pdf", "docx", "xls", "pptx", "wps"] {
+      assert!(
+        matches!(
+          check_doc_content(&[0, 1, 2], Some(declared.into())),
+          Err(UploadError::FormatMismatch { .. })
+        ),
+        "{declared}"
+      );
+    }
+
+    assert!(matches!(
+      check_doc_content(&docx, Some("xlsx".into())),
+      Err(UploadError::FormatMismatch { .. })
+    ));
+    assert!(matches!(
+      check_doc_content(b"%PDF-1.4", Some("docx".into())),
+      Err(UploadError::FormatMismatch { .. })
+    ));
+    assert!(matches!(check_doc_content(&ole, None), Err(UploadError::UnknownContent)));
+    assert!(matches!(check_doc_content(&[0, 1, 2], None), Err(UploadError::UnknownContent)));
+  }
+
+  /// 每个测试一个独立的临时目录
+  fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("upload-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn path_source(path: &Path) -> UploadSource {
+    UploadSource::Path(path.to_string_lossy().into_owned())
+  }
+
+  #[tokio::test]
+  async fn read_doc_from_path() {
+    let dir = temp_dir("doc-ok");
+    let path = dir.join("报告.PDF");
+    std::fs::write(&path, b"%PDF-1.7\n...").unwrap();
+
+    let file = read_doc(path_source(&path), None, Some("docx")).await.unwrap();
+    assert_eq!(file.filename, "报告.PDF");
+    assert_eq!(file.format, "pdf");
+    assert_eq!(file.bytes, b"%PDF-1.7\n...");
+
+    // 输出格式组合不对
+    let err = read_doc(path_source(&path), None, Some("xlsx")).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::UnsupportedOutput { .. })), "{err:?}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn read_doc_rejects_bad_inputs() {
+    let dir = temp_dir("doc-bad");
+
+    let empty = dir.join("empty.docx");
+    std::fs::write(&empty, b"").unwrap();
+    let err = read_doc(path_source(&empty), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::Empty(_))), "{err:?}");
+
+    // 稀疏文件：只设置长度，确认超限时不会去读内容
+    let big = dir.join("big.pdf");
+    std::fs::File::create(&big).unwrap().set_len(DOC_MAX_BYTES + 1).unwrap();
+    let err = read_doc(path_source(&big), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::TooLarge { .. })), "{err:?}");
+
+    let sub = dir.join("folder.pdf");
+    std::fs::create_dir_all(&sub).unwrap();
+    let err = read_doc(path_source(&sub), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::NotAFile(_))), "{err:?}");
+
+    // 扩展名不支持时文件都不用存在
+    let err = read_doc(path_source(&dir.join("setup.exe")), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::UnsupportedFormat { .. })), "{err:?}");
+
+    let err = read_doc(path_source(&dir.join("missing.pdf")), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::Io(_)), "{err:?}");
+
+    let fake = dir.join("fake.docx");
+    std::fs::write(&fake, b"%PDF-1.4").unwrap();
+    let err = read_doc(path_source(&fake), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::FormatMismatch { .. })), "{err:?}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn read_image_from_path() {
+    let dir = temp_dir("image");
+
+    let png = dir.join("shot.PNG");
+    std::fs::write(&png, b"\x89PNG\r\n").unwrap();
+    let file = read_image(path_source(&png), None).await.unwrap();
+    assert_eq!((file.filename.as_str(), file.format.as_str()), ("shot.PNG", "image/png"));
+
+    // 显式传入的 mime 优先于扩展名
+    let file = read_image(path_source(&png), Some("IMAGE/JPEG")).await.unwrap();
+    assert_eq!(file.format, "image/jpeg");
+
+    let err = read_image(path_source(&dir.join("anim.gif")), None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::UnsupportedFormat { .. })), "{err:?}");
+    let err = read_image(path_source(&png), Some("image/gif")).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::UnsupportedFormat { .. })), "{err:?}");
+
+    let big = dir.join("big.jpg");
+    std::fs::File::create(&big).unwrap().set_len(PIC_MAX_BYTES + 1).unwrap();
+    let err = read_image(path_source(&big), None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::TooLarge { .. })), "{err:?}");
+
+    let empty = dir.join("empty.webp");
+    std::fs::write(&empty, b"").unwrap();
+    let err = read_image(path_source(&empty), None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::Empty(_))), "{err:?}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn read_limited_checks_size_boundary() {
+    let dir = temp_dir("limited");
+    let path = dir.join("a.txt");
+    std::fs::write(&path, b"12345").unwrap();
+
+    assert_eq!(read_limited(&path, 5).await.unwrap(), b"12345");
+    let err = read_limited(&path, 4).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::TooLarge { size: 5, max: 4, .. })), "{err:?}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn bytes_source_uses_same_checks() {
+    let source = UploadSource::new(None, Some(Vec::new()), Some("a.pdf".to_string())).unwrap();
+    let err = read_doc(source, None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidFile(UploadError::Empty(_))), "{err:?}");
+
+    assert!(matches!(UploadSource::new(Some(" ".to_string()), None, None), Err(UploadError::NoInput)));
+  }
+
+  #[test]
+  fn check_output_format_cases() {
+    for (input, outputs) in DOC_OUTPUT_FORMATS {
+      for output in *outputs {
+        assert!(check_output_format(input, output).is_ok(), "{input} -> {output}");
+      }
+    }
+    assert!(check_output_format("pdf", " DOCX ").is_ok());
+
+    for (input, output) in [("xlsx", "docx"), ("pptx", "pdf"), ("txt", "docx"), ("exe", "docx")] {
+      assert!(
+        matches!(check_output_format(input, output), Err(UploadError::UnsupportedOutput { .. })),
+        "{input} -> {output}"
+      );
+    }
+  }
+}
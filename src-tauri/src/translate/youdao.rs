@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+
+use super::{from_native, to_native, LangMap, TranslateContext, TranslateRequest, TranslationProvider};
+use crate::{
+  commands::{
+    baidu_translate::TextTranslateResult,
+    youdao_translate::{self, YoudaoTextPayload},
+  },
+  error::AppError,
+  settings::ProxyService,
+};
+
+/// 统一语言代码（百度）-> 有道语言代码
+const LANG_MAP: LangMap = &[
+  ("auto", "auto"),
+  ("zh", "zh-CHS"),
+  ("cht", "zh-CHT"),
+  ("yue", "yue"),
+  ("en", "en"),
+  ("jp", "ja"),
+  ("kor", "ko"),
+  ("fra", "fr"),
+  ("spa", "es"),
+  ("de", "de"),
+  ("ru", "ru"),
+  ("pt", "pt"),
+  ("it", "it"),
+  ("ara", "ar"),
+  ("th", "th"),
+  ("vie", "vi"),
+  ("id", "id"),
+  ("may", "ms"),
+  ("nl", "nl"),
+  ("pl", "pl"),
+  ("tr", "tr"),
+  ("hi", "hi"),
+];
+
+pub struct YoudaoProvider;
+
+#[async_trait]
+impl TranslationProvider for YoudaoProvider {
+  async fn translate_text(
+    &self,
+    ctx: &TranslateContext<'_>,
+    req: &TranslateRequest,
+  ) -> Result<TextTranslateResult, AppError> {
+    let client = ctx.http.client(ctx.pool, ProxyService::Youdao).await?;
+    let keys = youdao_translate::get_youdao_keys(ctx.pool)?;
+    let payload = YoudaoTextPayload {
+      q: req.q.clone(),
+      from: to_native(LANG_MAP, &req.from),
+      to: to_native(LANG_MAP, &req.to),
+    };
+
+    let mut result = youdao_translate::text_translate(
+      &client,
+      youdao_translate::YOUDAO_TEXT_URL,
+      &keys,
+      &payload,
+    )
+    .await?;
+    result.from = from_native(LANG_MAP, &result.from);
+    result.to = from_native(LANG_MAP, &result.to);
+    Ok(result)
+  }
+
+  fn supported_languages(&self) -> Vec<&'static str> {
+    LANG_MAP.iter().map(|(code, _)| *code).filter(|c| *c != "auto").collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::translate::{provider, ProviderKind};
+
+  #[test]
+  fn lang_map_round_trips() {
+    let baidu = provider(ProviderKind::Baidu).supported_languages();
+    for (unified, native) in LANG_MAP {
+      assert!(*unified == "auto" || baidu.contains(unified), "{unified} is not a Baidu code");
+      assert_eq!(to_native(LANG_MAP, unified), *native);
+      assert_eq!(from_native(LANG_MAP, native), *unified, "{native}");
+    }
+  }
+}
@@ -67,3 +67,21 @@ export type NetworkProxyForm = {
   /** 域名绕过 */
   noProxy?: string[]
 }
+
+export const ProxyService = {
+  baidu: 'baidu',
+  youdao: 'youdao',
+  deepl: 'deepl',
+  github: 'github',
+  openai: 'openai',
+  deepseek: 'deepseek',
+  qwen: 'qwen',
+  doubao: 'doubao',
+  wenxin: 'wenxin',
+  yuanbao: 'yuanbao',
+} as const
+
+export type ProxyService = (typeof ProxyService)[keyof typeof ProxyService]
+
+/** 按服务覆盖的代理设置，未配置的服务使用全局设置 */
+export type NetworkProxyOverrides = Partial<Record<ProxyService, NetworkProxyForm>>
@@ -51,3 +51,24 @@ export interface BaiduDocQueryRaw {
   error_code?: number
   error_msg?: string
 }
+
+/**
+ * =========================
+ * 文档翻译后台任务（Rust DocJob，随 doc-translate:progress 事件推送）
+ * =========================
+ */
+export interface DocJob {
+  id: string
+  from: string
+  to: string
+  filename?: string | null
+  format: string
+  output_format?: string | null
+  status: 'running' | 'downloading' | 'succeeded' | 'failed'
+  remote_status?: string | null
+  reason?: string | null
+  /** 译文下载到本地的路径 */
+  output_path?: string | null
+  created_at: number
+  updated_at: number
+}